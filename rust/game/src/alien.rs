@@ -0,0 +1,266 @@
+//! The aliens, how they move and the formations they come in.
+
+use crate::canvas::SIZE;
+use crate::rng::Rng;
+
+/// Most aliens a wave can have.
+pub const MAX_ALIENS: usize = 6;
+
+/// Lowest row a diving alien comes down to, one above the top of the ship.
+const DIVE_FLOOR: usize = 5;
+
+/// How the aliens of a wave move.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FormationMode {
+    /// Every alien moves on its own, following its [Pattern].
+    Bounce,
+    /// The aliens move as one block and drop a row towards the ship whenever the block reaches an edge.
+    Descend,
+}
+
+impl FormationMode {
+    /// The mode that is not this one.
+    pub fn other(self) -> Self {
+        match self {
+            FormationMode::Bounce => FormationMode::Descend,
+            FormationMode::Descend => FormationMode::Bounce,
+        }
+    }
+}
+
+/// How a single alien moves on its own.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pattern {
+    /// Back and forth between the edges of its row.
+    PingPong,
+    /// Like ping-pong, but also up and down a row at every step.
+    ZigZag,
+    /// A random step left, right or nowhere.
+    RandomWalk,
+    /// Heads for the ship's column and dives towards it, climbing back when the ship gets away.
+    Dive,
+    /// Stays put, but now and then jumps to a random spot of its row.
+    Teleport,
+}
+
+/// The patterns in the order the waves introduce them.
+const PATTERNS: [Pattern; 5] = [
+    Pattern::PingPong,
+    Pattern::ZigZag,
+    Pattern::RandomWalk,
+    Pattern::Dive,
+    Pattern::Teleport,
+];
+
+/// Start position and direction of each alien slot, `(x, y, increment)`.
+/// Wave `n` fills the first `n` slots, so every wave brings one more alien until the pool is full.
+/// The column and direction are only where a [FormationMode::Bounce] alien would be without randomness.
+const BOUNCE_SLOTS: [(usize, usize, i8); MAX_ALIENS] = [
+    (0, 0, 1),
+    (7, 0, -1),
+    (2, 1, 1),
+    (5, 1, -1),
+    (4, 2, 1),
+    (3, 2, -1),
+];
+
+/// Slots of a [FormationMode::Descend] block, which all move in the same direction.
+const BLOCK_SLOTS: [(usize, usize, i8); MAX_ALIENS] = [
+    (1, 0, 1),
+    (3, 0, 1),
+    (5, 0, 1),
+    (2, 1, 1),
+    (4, 1, 1),
+    (6, 1, 1),
+];
+
+/// One alien of the pool.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Alien {
+    pub x: usize,
+    pub y: usize,
+    /// Where the alien was before its last step.
+    pub prev: (usize, usize),
+    /// The row the alien belongs on, which zigzags and dives return to.
+    pub row: usize,
+    /// Current direction of movement
+    pub increment: i8,
+    pub pattern: Pattern,
+    /// Dead aliens are free slots of the pool.
+    pub alive: bool,
+}
+
+impl Alien {
+    /// An empty slot.
+    pub const DEAD: Alien = Alien {
+        x: 0,
+        y: 0,
+        prev: (0, 0),
+        row: 0,
+        increment: 1,
+        pattern: Pattern::PingPong,
+        alive: false,
+    };
+
+    /// Whether a living alien is at (`x`, `y`), or was there before its last step.
+    pub fn occupies(&self, x: usize, y: usize) -> bool {
+        self.alive && ((self.x, self.y) == (x, y) || self.prev == (x, y))
+    }
+
+    /// Whether the next step in the current direction would leave the matrix.
+    pub fn at_edge(&self) -> bool {
+        (self.x < 1 && self.increment < 0) || (self.x >= 7 && self.increment > 0)
+    }
+
+    /// Moves one spot in the current direction.
+    pub fn advance(&mut self) {
+        if self.increment > 0 {
+            self.x += 1;
+        } else {
+            self.x -= 1;
+        }
+    }
+
+    /// Turns around and comes one row closer to the ship.
+    pub fn drop_row(&mut self) {
+        self.increment = -self.increment;
+        self.y += 1;
+        self.row += 1;
+    }
+
+    /// Moves one step following the [Pattern]. `ship_x` is the column diving aliens aim for.
+    pub fn step(&mut self, rng: &mut Rng, ship_x: usize) {
+        match self.pattern {
+            Pattern::PingPong => self.bounce(),
+            Pattern::ZigZag => {
+                self.bounce();
+                self.y = self.row + self.x % 2;
+            }
+            Pattern::RandomWalk => self.x = (self.x + rng.below(3)).saturating_sub(1).min(SIZE - 1),
+            Pattern::Dive => {
+                if self.x == ship_x && self.y < DIVE_FLOOR {
+                    self.y += 1;
+                } else if self.x != ship_x && self.y > self.row {
+                    self.y -= 1;
+                } else if self.x < ship_x {
+                    self.x += 1;
+                } else if self.x > ship_x {
+                    self.x -= 1;
+                }
+            }
+            Pattern::Teleport => {
+                if rng.one_in(4) {
+                    self.x = rng.below(SIZE);
+                }
+            }
+        }
+    }
+
+    /// Checks the movement direction and moves to a new spot, bouncing off the edges.
+    fn bounce(&mut self) {
+        // Check if running to edges
+        if self.x < 1 {
+            self.increment = 1;
+        }
+        if self.x >= 7 {
+            self.increment = -1;
+        }
+
+        self.advance();
+    }
+}
+
+/// The pattern of the alien in pool slot `slot` of wave `wave`.
+/// The first waves introduce one pattern each, later waves mix them.
+fn pattern(wave: u16, slot: usize) -> Pattern {
+    let wave = usize::from(wave.max(1));
+    if wave <= PATTERNS.len() {
+        PATTERNS[wave - 1]
+    } else {
+        PATTERNS[(wave + slot) % PATTERNS.len()]
+    }
+}
+
+/// The aliens of wave `wave`, counting from 1, laid out for moving in `mode`.
+/// A [FormationMode::Descend] block moves as a whole, so its aliens have no pattern of their own.
+///
+/// Bouncing aliens spawn in a random column heading a random way, a block spawns shifted a random step sideways.
+pub fn formation(wave: u16, mode: FormationMode, rng: &mut Rng) -> [Alien; MAX_ALIENS] {
+    let slots = match mode {
+        FormationMode::Bounce => &BOUNCE_SLOTS,
+        FormationMode::Descend => &BLOCK_SLOTS,
+    };
+    let shift = rng.below(3);
+    let mut aliens = [Alien::DEAD; MAX_ALIENS];
+    let count = usize::from(wave).clamp(1, MAX_ALIENS);
+    for (slot, (alien, &(x, y, increment))) in aliens.iter_mut().zip(slots.iter()).take(count).enumerate() {
+        let (x, increment) = match mode {
+            FormationMode::Bounce => (rng.below(SIZE), if rng.one_in(2) { 1 } else { -1 }),
+            FormationMode::Descend => (x + shift - 1, increment),
+        };
+        *alien = Alien {
+            x,
+            y,
+            prev: (x, y),
+            row: y,
+            increment,
+            pattern: match mode {
+                FormationMode::Bounce => pattern(wave, slot),
+                FormationMode::Descend => Pattern::PingPong,
+            },
+            alive: true,
+        };
+    }
+    aliens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alive(aliens: &[Alien]) -> usize {
+        aliens.iter().filter(|alien| alien.alive).count()
+    }
+
+    #[test]
+    fn every_wave_brings_one_more_alien_until_the_pool_is_full() {
+        let mut rng = Rng::new(1);
+        for wave in 1..=8 {
+            let aliens = formation(wave, FormationMode::Bounce, &mut rng);
+            assert_eq!(alive(&aliens), usize::from(wave).min(MAX_ALIENS));
+        }
+    }
+
+    #[test]
+    fn same_seed_same_formation() {
+        let a = formation(4, FormationMode::Bounce, &mut Rng::new(9));
+        let b = formation(4, FormationMode::Bounce, &mut Rng::new(9));
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn bounce_aliens_spawn_on_the_matrix_in_their_rows() {
+        let mut rng = Rng::new(3);
+        for _ in 0..50 {
+            let aliens = formation(6, FormationMode::Bounce, &mut rng);
+            for (alien, &(_, y, _)) in aliens.iter().zip(BOUNCE_SLOTS.iter()) {
+                assert!(alien.x < SIZE);
+                assert_eq!((alien.y, alien.row, alien.prev), (y, y, (alien.x, y)));
+            }
+        }
+    }
+
+    #[test]
+    fn descend_block_is_shifted_as_a_whole() {
+        let mut rng = Rng::new(5);
+        for _ in 0..20 {
+            let aliens = formation(6, FormationMode::Descend, &mut rng);
+            let shift = aliens[0].x as isize - BLOCK_SLOTS[0].0 as isize;
+            assert!((-1..=1).contains(&shift));
+            for (alien, &(x, y, increment)) in aliens.iter().zip(BLOCK_SLOTS.iter()) {
+                assert_eq!((alien.x as isize - x as isize, alien.y), (shift, y));
+                assert_eq!((alien.increment, alien.pattern), (increment, Pattern::PingPong));
+            }
+        }
+    }
+}
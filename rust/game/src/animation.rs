@@ -0,0 +1,109 @@
+//! Short full-screen animations, drawn one frame at a time.
+
+use crate::canvas::{Canvas, SIZE};
+
+/// An animation that loops through a few frames.
+///
+/// The frames can be drawn ahead of time, e.g. into framebuffer pages that the display then plays back at
+/// [Animation::fps].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Animation {
+    /// Rings spreading out from the middle, around the victory signal.
+    Victory,
+    /// A fireball growing from the ship until it fills the matrix.
+    Explosion,
+}
+
+impl Animation {
+    /// Number of frames in one loop.
+    pub const fn frames(self) -> usize {
+        match self {
+            Animation::Victory => 4,
+            Animation::Explosion => 4,
+        }
+    }
+
+    /// Frames shown per second.
+    pub const fn fps(self) -> u32 {
+        match self {
+            Animation::Victory => 8,
+            Animation::Explosion => 6,
+        }
+    }
+
+    /// Draw frame `frame`, counting from 0 and wrapping around after the last one.
+    /// Only the pixels of the animation are set, clear the canvas first for the frame alone.
+    pub fn draw_frame(self, canvas: &mut impl Canvas, frame: usize) {
+        let frame = frame % self.frames();
+        match self {
+            Animation::Victory => draw_victory(canvas, frame),
+            Animation::Explosion => draw_explosion(canvas, frame),
+        }
+    }
+}
+
+/// Draw pixels to form a victory signal, inside a square ring that grows with `frame`.
+fn draw_victory(canvas: &mut impl Canvas, frame: usize) {
+    // Ring half a pixel off the middle of the matrix, measured in half pixels
+    let radius = 2 * frame + 1;
+    for x in 0..SIZE {
+        for y in 0..SIZE {
+            if (2 * x).abs_diff(7).max((2 * y).abs_diff(7)) == radius {
+                canvas.set_pixel(x, y, 0, 80, 40);
+            }
+        }
+    }
+
+    let r = 0;
+    let g = 200;
+    let b = 100;
+
+    canvas.set_pixel(4, 3, r, g, b);
+    canvas.set_pixel(5, 1, r, g, b);
+    canvas.set_pixel(4, 4, r, g, b);
+    canvas.set_pixel(5, 2, r, g, b);
+    canvas.set_pixel(1, 2, r, g, b);
+    canvas.set_pixel(2, 4, r, g, b);
+}
+
+/// Draw some colours to represent player ship "explosion": a fireball from the ship for the first frames, then the
+/// whole matrix.
+fn draw_explosion(canvas: &mut impl Canvas, frame: usize) {
+    if frame < 3 {
+        let reach = 2 * frame + 2;
+        for x in 0..SIZE {
+            for y in 0..SIZE {
+                let distance = x.abs_diff(3) + y.abs_diff(7);
+                match distance {
+                    _ if distance > reach => {}
+                    0..=1 => canvas.set_pixel(x, y, 255, 255, 200),
+                    2..=3 => canvas.set_pixel(x, y, 255, 200, 0),
+                    _ => canvas.set_pixel(x, y, 255, 60, 0),
+                }
+            }
+        }
+        return;
+    }
+
+    let mut r: u8 = 1;
+    let mut g: u8 = 1;
+    let mut b: u8 = 1;
+    for i in 0..7 {
+        for j in 0..7 {
+            r += 2;
+            g += 4;
+            b += 6;
+            // Prevent colour overflow
+            if r > 240 {
+                r = 0;
+            }
+            if g > 240 {
+                g = 0;
+            }
+            if b > 240 {
+                b = 0;
+            }
+            canvas.set_pixel(i, j, r, g, b);
+        }
+    }
+}
@@ -0,0 +1,174 @@
+//! The boss that comes after every few waves.
+
+use crate::canvas::{Canvas, SIZE};
+
+/// Pixels of the boss relative to its top left corner.
+const SPRITE: [(usize, usize); 5] = [(0, 0), (1, 0), (2, 0), (0, 1), (2, 1)];
+/// Width of the sprite.
+const WIDTH: usize = 3;
+/// Ticks the boss shows white after a hit.
+const FLASH_TICKS: u8 = 2;
+
+/// A big alien that takes several hits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Boss {
+    pub x: usize,
+    pub y: usize,
+    /// Column the boss was in before its last step.
+    pub prev_x: usize,
+    /// Hits still needed to destroy the boss, 0 when there is none.
+    pub hp: u16,
+    /// `hp` at the start of the fight, for the hit points bar.
+    pub max_hp: u16,
+    /// Current direction of movement
+    pub increment: i8,
+    /// Ticks left of the damage flash.
+    flash: u8,
+}
+
+impl Boss {
+    /// No boss on the matrix.
+    pub const NONE: Boss = Boss {
+        x: 0,
+        y: 0,
+        prev_x: 0,
+        hp: 0,
+        max_hp: 0,
+        increment: 1,
+        flash: 0,
+    };
+
+    /// A boss with `hp` hit points, entering below the hit points bar.
+    pub fn new(hp: u16) -> Self {
+        Boss {
+            x: (SIZE - WIDTH) / 2,
+            y: 1,
+            prev_x: (SIZE - WIDTH) / 2,
+            hp,
+            max_hp: hp,
+            ..Boss::NONE
+        }
+    }
+
+    pub fn alive(&self) -> bool {
+        self.hp > 0
+    }
+
+    /// The matrix pixels the boss covers.
+    pub fn pixels(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        SPRITE.iter().map(move |&(dx, dy)| (self.x + dx, self.y + dy))
+    }
+
+    /// Whether a living boss covers (`x`, `y`), or covered it before its last step.
+    pub fn covers(&self, x: usize, y: usize) -> bool {
+        let covered_by = |left: usize| SPRITE.iter().any(|&(dx, dy)| (left + dx, self.y + dy) == (x, y));
+        self.alive() && (covered_by(self.x) || covered_by(self.prev_x))
+    }
+
+    /// Take one hit, returns whether it was the last one.
+    pub fn hit(&mut self) -> bool {
+        self.hp = self.hp.saturating_sub(1);
+        self.flash = FLASH_TICKS;
+        self.hp == 0
+    }
+
+    /// Moves one spot sideways, bouncing off the edges, and lets the damage flash fade.
+    pub fn step(&mut self) {
+        self.flash = self.flash.saturating_sub(1);
+        self.prev_x = self.x;
+
+        if self.x < 1 {
+            self.increment = 1;
+        }
+        if self.x >= SIZE - WIDTH {
+            self.increment = -1;
+        }
+        if self.increment > 0 {
+            self.x += 1;
+        } else {
+            self.x -= 1;
+        }
+    }
+
+    /// Where the shots of the boss start, below both of its legs.
+    pub fn guns(&self) -> [(usize, usize); 2] {
+        [(self.x, self.y + 2), (self.x + WIDTH - 1, self.y + 2)]
+    }
+
+    /// Draws the boss, white while flashing, and its hit points as a bar on the top row.
+    pub fn draw(&self, canvas: &mut impl Canvas) {
+        if !self.alive() {
+            return;
+        }
+        let (r, g, b) = if self.flash > 0 { (255, 255, 255) } else { (255, 120, 0) };
+        for (x, y) in self.pixels() {
+            canvas.set_pixel(x, y, r, g, b);
+        }
+
+        // Rounded up, so the bar is gone only with the boss
+        let bar = (usize::from(self.hp) * SIZE).div_ceil(usize::from(self.max_hp));
+        for x in 0..bar {
+            canvas.set_pixel(x, 0, 150, 0, 0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canvas::Framebuffer;
+
+    fn drawn(boss: &Boss) -> Framebuffer {
+        let mut canvas = Framebuffer::new();
+        boss.draw(&mut canvas);
+        canvas
+    }
+
+    /// Pixels lit on the hit points bar.
+    fn bar_width(canvas: &Framebuffer) -> usize {
+        (0..SIZE).filter(|&x| canvas.pixel(x, 0) != (0, 0, 0)).count()
+    }
+
+    #[test]
+    fn every_hit_takes_one_hit_point() {
+        let mut boss = Boss::new(3);
+        assert!(!boss.hit());
+        assert!(!boss.hit());
+        assert_eq!(boss.hp, 1);
+        assert!(boss.hit());
+        assert!(!boss.alive());
+    }
+
+    #[test]
+    fn hit_flashes_white_for_a_while() {
+        let mut boss = Boss::new(3);
+        boss.hit();
+        for _ in 0..FLASH_TICKS {
+            assert_eq!(drawn(&boss).pixel(boss.x, boss.y), (255, 255, 255));
+            boss.step();
+        }
+        assert_eq!(drawn(&boss).pixel(boss.x, boss.y), (255, 120, 0));
+    }
+
+    #[test]
+    fn bar_shrinks_with_the_hit_points_and_is_gone_only_with_the_boss() {
+        let mut boss = Boss::new(8);
+        assert_eq!(bar_width(&drawn(&boss)), SIZE);
+        boss.hp = 4;
+        assert_eq!(bar_width(&drawn(&boss)), 4);
+        boss.hp = 1;
+        assert_eq!(bar_width(&drawn(&boss)), 1);
+        boss.hp = 0;
+        assert_eq!(drawn(&boss), Framebuffer::new());
+    }
+
+    #[test]
+    fn guns_are_below_both_legs() {
+        let boss = Boss::new(3);
+        for (x, y) in boss.guns() {
+            assert!(boss.covers(x, y - 1));
+            assert!(!boss.covers(x, y));
+        }
+        assert_eq!(boss.guns()[1].0 - boss.guns()[0].0, WIDTH - 1);
+    }
+}
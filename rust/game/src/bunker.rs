@@ -0,0 +1,92 @@
+//! Bunkers between the aliens and the ship.
+
+use crate::canvas::{Canvas, SIZE};
+
+/// Row the bunkers are on, just above the ship.
+pub const BUNKER_ROW: usize = 5;
+/// Columns with a bunker pixel at the start of a game.
+const COLUMNS: [usize; 4] = [1, 2, 5, 6];
+/// Hits a fresh bunker pixel takes.
+const STRENGTH: u8 = 3;
+
+/// The bunker pixels, worn down by the shots of both sides.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Bunkers {
+    /// Hits left of the pixel in each column of [BUNKER_ROW], 0 where there is none.
+    strength: [u8; SIZE],
+}
+
+impl Bunkers {
+    /// The bunkers at the start of a game.
+    pub const fn new() -> Self {
+        let mut strength = [0; SIZE];
+        let mut i = 0;
+        while i < COLUMNS.len() {
+            strength[COLUMNS[i]] = STRENGTH;
+            i += 1;
+        }
+        Bunkers { strength }
+    }
+
+    /// Whether a bunker pixel is at (`x`, `y`).
+    pub fn covers(&self, x: usize, y: usize) -> bool {
+        y == BUNKER_ROW && self.strength.get(x).is_some_and(|&strength| strength > 0)
+    }
+
+    /// Stops a shot at (`x`, `y`) if a bunker pixel is there, which then takes the hit.
+    /// Returns whether the shot was stopped.
+    pub fn absorb(&mut self, x: usize, y: usize) -> bool {
+        match self.strength.get_mut(x) {
+            Some(strength) if y == BUNKER_ROW && *strength > 0 => {
+                *strength -= 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Draws the bunker pixels, darker and redder the more hits they have taken.
+    pub fn draw(&self, canvas: &mut impl Canvas) {
+        for (x, &strength) in self.strength.iter().enumerate() {
+            let (r, g, b) = match strength {
+                0 => continue,
+                1 => (120, 30, 0),
+                2 => (150, 100, 40),
+                _ => (150, 150, 150),
+            };
+            canvas.set_pixel(x, BUNKER_ROW, r, g, b);
+        }
+    }
+}
+
+impl Default for Bunkers {
+    fn default() -> Self {
+        Bunkers::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_shot_takes_one_hit_until_the_pixel_is_gone() {
+        let mut bunkers = Bunkers::new();
+        for _ in 0..STRENGTH {
+            assert!(bunkers.covers(1, BUNKER_ROW));
+            assert!(bunkers.absorb(1, BUNKER_ROW));
+        }
+        assert!(!bunkers.covers(1, BUNKER_ROW));
+        assert!(!bunkers.absorb(1, BUNKER_ROW));
+        // The pixel next to it is untouched
+        assert!(bunkers.covers(2, BUNKER_ROW));
+    }
+
+    #[test]
+    fn shots_pass_where_there_is_no_bunker() {
+        let mut bunkers = Bunkers::new();
+        assert!(!bunkers.absorb(0, BUNKER_ROW));
+        assert!(!bunkers.absorb(1, BUNKER_ROW - 1));
+        assert_eq!(bunkers, Bunkers::new());
+    }
+}
@@ -0,0 +1,55 @@
+//! Drawing surfaces for the game graphics.
+
+/// Width and height of the LED matrix.
+pub const SIZE: usize = 8;
+
+/// Something the game can draw its 8x8 picture on.
+pub trait Canvas {
+    /// Set the color of one pixel.
+    /// Coordinates outside of the 8x8 matrix are ignored.
+    fn set_pixel(&mut self, x: usize, y: usize, r: u8, g: u8, b: u8);
+
+    /// Turn every pixel off.
+    fn clear(&mut self) {
+        for x in 0..SIZE {
+            for y in 0..SIZE {
+                self.set_pixel(x, y, 0, 0, 0);
+            }
+        }
+    }
+}
+
+/// A picture in memory, e.g. for checking what the game draws.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Framebuffer {
+    /// Indices are x, y and color (r, g, b).
+    dots: [[[u8; 3]; SIZE]; SIZE],
+}
+
+impl Framebuffer {
+    pub const fn new() -> Self {
+        Framebuffer {
+            dots: [[[0; 3]; SIZE]; SIZE],
+        }
+    }
+
+    /// The color of one pixel as `(r, g, b)`.
+    pub fn pixel(&self, x: usize, y: usize) -> (u8, u8, u8) {
+        let [r, g, b] = self.dots[x][y];
+        (r, g, b)
+    }
+}
+
+impl Default for Framebuffer {
+    fn default() -> Self {
+        Framebuffer::new()
+    }
+}
+
+impl Canvas for Framebuffer {
+    fn set_pixel(&mut self, x: usize, y: usize, r: u8, g: u8, b: u8) {
+        if x < SIZE && y < SIZE {
+            self.dots[x][y] = [r, g, b];
+        }
+    }
+}
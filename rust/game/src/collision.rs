@@ -0,0 +1,56 @@
+//! Collisions of things that move more than a pixel between checks.
+//!
+//! Checking only where a shot ends up after a tick lets a shot faster than one row per tick jump over an alien, and
+//! lets a shot and an alien swap places unnoticed. Instead the whole path a shot moved along during the tick is
+//! checked, pixel by pixel in the order it was travelled, against where the things it can hit were before and
+//! after their own moves.
+
+use crate::canvas::SIZE;
+
+/// A straight move during one tick.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Sweep {
+    pub from: (usize, usize),
+    pub to: (usize, usize),
+}
+
+impl Sweep {
+    /// The pixels on the way from `from` to `to`, both included, leaving out any outside the matrix.
+    pub fn cells(self) -> impl Iterator<Item = (usize, usize)> {
+        let (x0, y0) = (self.from.0 as isize, self.from.1 as isize);
+        let (dx, dy) = (self.to.0 as isize - x0, self.to.1 as isize - y0);
+        let steps = dx.abs().max(dy.abs()).max(1);
+        (0..=steps)
+            .map(move |i| (x0 + dx * i / steps, y0 + dy * i / steps))
+            .filter(|&(x, y)| (0..SIZE as isize).contains(&x) && (0..SIZE as isize).contains(&y))
+            .map(|(x, y)| (x as usize, y as usize))
+    }
+
+    /// The first pixel on the way for which `hits` is true.
+    pub fn first_hit(self, hits: impl FnMut(&(usize, usize)) -> bool) -> Option<(usize, usize)> {
+        self.cells().find(hits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cells_follow_the_way_in_order() {
+        let sweep = Sweep { from: (3, 6), to: (3, 3) };
+        assert!(sweep.cells().eq([(3, 6), (3, 5), (3, 4), (3, 3)]));
+    }
+
+    #[test]
+    fn cells_outside_the_matrix_are_left_out() {
+        let sweep = Sweep { from: (2, 6), to: (2, 9) };
+        assert!(sweep.cells().eq([(2, 6), (2, 7)]));
+    }
+
+    #[test]
+    fn first_hit_is_the_first_on_the_way() {
+        let sweep = Sweep { from: (1, 7), to: (1, 0) };
+        assert_eq!(sweep.first_hit(|&(_, y)| y == 2 || y == 5), Some((1, 5)));
+    }
+}
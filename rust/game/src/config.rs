@@ -0,0 +1,64 @@
+//! Tunable rules of the game.
+
+use crate::alien::FormationMode;
+
+/// Settings that stay the same for every game played with a [GameState](crate::GameState).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Config {
+    /// Ships the player has in a game, the game ends when the shield of the last one is gone.
+    pub lives: u16,
+    /// Game ticks a new ship can't be hit after respawning.
+    pub invulnerable_ticks: u16,
+    /// How the aliens move.
+    pub formation: FormationMode,
+    /// Whether every other wave moves the other way than `formation`, starting from the second.
+    pub alternate_formation: bool,
+    /// Game ticks between two steps of a [FormationMode::Descend] block.
+    pub descend_step_ticks: u16,
+    /// Player shots in flight at once, up to [MAX_SHOTS](crate::MAX_SHOTS).
+    pub max_shots: usize,
+    /// Rows a player shot moves up per tick.
+    pub shot_speed: u8,
+    /// Game ticks between two alien shots.
+    pub alien_fire_ticks: u16,
+    /// Alien shots in flight at once, up to [MAX_SHOTS](crate::MAX_SHOTS).
+    pub max_alien_shots: usize,
+    /// Rows an alien shot moves down per tick.
+    pub alien_shot_speed: u8,
+    /// Every this many waves the boss comes instead of the aliens, 0 for never.
+    pub boss_every: u16,
+    /// Hits it takes to destroy the boss.
+    pub boss_hp: u16,
+    /// One in this many destroyed aliens drops a power-up, 0 for none at all.
+    pub drop_chance: usize,
+    /// Game ticks a rapid fire or spread shot power-up lasts.
+    pub power_up_ticks: u16,
+}
+
+impl Config {
+    /// The settings used on the board.
+    pub const fn new() -> Self {
+        Config {
+            lives: 3,
+            invulnerable_ticks: 30,
+            formation: FormationMode::Bounce,
+            alternate_formation: true,
+            descend_step_ticks: 5,
+            max_shots: 2,
+            shot_speed: 1,
+            alien_fire_ticks: 12,
+            max_alien_shots: 3,
+            alien_shot_speed: 1,
+            boss_every: 4,
+            boss_hp: 8,
+            drop_chance: 4,
+            power_up_ticks: 100,
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config::new()
+    }
+}
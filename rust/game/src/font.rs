@@ -0,0 +1,39 @@
+//! A 3x5 pixel font for the digits shown on the matrix.
+
+use crate::canvas::Canvas;
+
+/// Digits 0-9, one row of three pixels per byte, most significant of the three bits on the left.
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b011, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b010, 0b010, 0b010],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+
+/// Draw `digit` (0-9) with its top left corner at (`x`, `y`).
+pub fn draw_digit(canvas: &mut impl Canvas, digit: u8, x: usize, y: usize, (r, g, b): (u8, u8, u8)) {
+    for (row, bits) in DIGITS[usize::from(digit % 10)].iter().enumerate() {
+        for column in 0..3 {
+            if bits & (0b100 >> column) != 0 {
+                canvas.set_pixel(x + column, y + row, r, g, b);
+            }
+        }
+    }
+}
+
+/// Draw `number` as two digits, or one centered digit when below 10. Numbers above 99 show as 99.
+pub fn draw_number(canvas: &mut impl Canvas, number: u16, color: (u8, u8, u8)) {
+    let number = number.min(99) as u8;
+    if number < 10 {
+        draw_digit(canvas, number, 2, 1, color);
+    } else {
+        draw_digit(canvas, number / 10, 0, 1, color);
+        draw_digit(canvas, number % 10, 4, 1, color);
+    }
+}
@@ -0,0 +1,29 @@
+//! Rules and graphics of the alien shooter, independent of the hardware.
+//!
+//! The crate is `no_std` and knows nothing about the Pynq board: the game is driven by calling [GameState]
+//! methods from whatever plays the role of the interrupt handlers, and drawn onto anything that implements
+//! [Canvas]. The same core runs on the board, in the host simulator and in unit tests.
+
+#![no_std]
+
+mod alien;
+mod animation;
+mod boss;
+mod bunker;
+mod canvas;
+mod collision;
+mod config;
+mod font;
+mod powerup;
+mod projectile;
+mod rng;
+mod state;
+
+pub use alien::{FormationMode, Pattern};
+pub use animation::Animation;
+pub use canvas::{Canvas, Framebuffer};
+pub use config::Config;
+pub use powerup::PowerUp;
+pub use projectile::MAX_SHOTS;
+pub use rng::Rng;
+pub use state::{Event, GameState, Phase};
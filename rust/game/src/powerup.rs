@@ -0,0 +1,42 @@
+//! Power-ups dropped by destroyed aliens.
+
+use crate::rng::Rng;
+
+/// What catching a power-up does.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PowerUp {
+    /// For a while as many bullets can fly as the pool has room for.
+    RapidFire,
+    /// For a while every shot is three bullets side by side.
+    SpreadShot,
+    /// One shield back at once.
+    ShieldRecharge,
+}
+
+impl PowerUp {
+    /// Any of the power-ups, equally likely.
+    pub fn random(rng: &mut Rng) -> Self {
+        match rng.below(3) {
+            0 => PowerUp::RapidFire,
+            1 => PowerUp::SpreadShot,
+            _ => PowerUp::ShieldRecharge,
+        }
+    }
+
+    /// Color of the falling power-up, and of the ship while its effect lasts.
+    pub fn color(self) -> (u8, u8, u8) {
+        match self {
+            PowerUp::RapidFire => (255, 200, 0),
+            PowerUp::SpreadShot => (0, 200, 200),
+            PowerUp::ShieldRecharge => (50, 100, 150),
+        }
+    }
+}
+
+/// A power-up falling towards the ship.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Pickup {
+    pub x: usize,
+    pub y: usize,
+    pub power_up: PowerUp,
+}
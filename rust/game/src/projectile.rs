@@ -0,0 +1,108 @@
+//! Shots flying over the matrix, kept in fixed-size pools.
+
+use crate::collision::Sweep;
+
+/// Slots in a pool of shots, the most any setting can have in flight at once.
+pub const MAX_SHOTS: usize = 4;
+
+/// One shot of a pool.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Projectile {
+    pub x: usize,
+    pub y: usize,
+    /// Row the projectile was on before its last step.
+    pub from_y: usize,
+    /// Rows moved per tick, negative is up.
+    pub velocity: i8,
+    /// Inactive projectiles are free slots of the pool.
+    pub active: bool,
+    /// Whether a bullet of the player costs shield when it misses, only one bullet of a spread does.
+    pub counts_miss: bool,
+}
+
+impl Projectile {
+    /// An empty slot.
+    pub const INACTIVE: Projectile = Projectile {
+        x: 0,
+        y: 0,
+        from_y: 0,
+        velocity: 0,
+        active: false,
+        counts_miss: true,
+    };
+
+    /// Moves the projectile by its velocity.
+    /// Shots going up stop on row 0, where the game decides what they hit. Shots going down may end up below the
+    /// bottom row, the game removes them after checking their way there.
+    pub fn step(&mut self) {
+        let rows = usize::from(self.velocity.unsigned_abs());
+        self.from_y = self.y;
+        if self.velocity < 0 {
+            self.y = self.y.saturating_sub(rows);
+        } else {
+            self.y += rows;
+        }
+    }
+
+    /// The way moved in the last step.
+    pub fn sweep(&self) -> Sweep {
+        Sweep {
+            from: (self.x, self.from_y),
+            to: (self.x, self.y),
+        }
+    }
+}
+
+/// A fixed-size pool of projectiles.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Shots {
+    slots: [Projectile; MAX_SHOTS],
+}
+
+impl Shots {
+    /// A pool with nothing in flight.
+    pub const fn new() -> Self {
+        Shots {
+            slots: [Projectile::INACTIVE; MAX_SHOTS],
+        }
+    }
+
+    /// Launch a projectile from (`x`, `y`), unless `limit` of them are already in flight.
+    /// Returns the projectile launched.
+    pub fn fire(&mut self, limit: usize, x: usize, y: usize, velocity: i8) -> Option<&mut Projectile> {
+        if self.iter().count() >= limit {
+            return None;
+        }
+        let slot = self.slots.iter_mut().find(|slot| !slot.active)?;
+        *slot = Projectile {
+            x,
+            y,
+            from_y: y,
+            velocity,
+            active: true,
+            counts_miss: true,
+        };
+        Some(slot)
+    }
+
+    /// Remove every projectile.
+    pub fn clear(&mut self) {
+        *self = Shots::new();
+    }
+
+    /// The projectiles in flight.
+    pub fn iter(&self) -> impl Iterator<Item = &Projectile> {
+        self.slots.iter().filter(|shot| shot.active)
+    }
+
+    /// The projectiles in flight, for moving or removing them.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Projectile> {
+        self.slots.iter_mut().filter(|shot| shot.active)
+    }
+}
+
+impl Default for Shots {
+    fn default() -> Self {
+        Shots::new()
+    }
+}
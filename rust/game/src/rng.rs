@@ -0,0 +1,67 @@
+//! A small deterministic random number generator.
+
+/// Xorshift generator with 32 bits of state. The same seed always gives the same numbers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rng {
+    state: u32,
+}
+
+impl Rng {
+    /// A generator starting from `seed`. Xorshift gets stuck on zero, so a zero seed is replaced.
+    pub const fn new(seed: u32) -> Self {
+        Rng {
+            state: if seed == 0 { 0x9E37_79B9 } else { seed },
+        }
+    }
+
+    /// The next number of the sequence.
+    pub fn next_u32(&mut self) -> u32 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.state = x;
+        x
+    }
+
+    /// A number in `0..n`, `n` must not be zero.
+    pub fn below(&mut self, n: usize) -> usize {
+        self.next_u32() as usize % n
+    }
+
+    /// True once in `n` calls on average.
+    pub fn one_in(&mut self, n: usize) -> bool {
+        self.below(n) == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_numbers() {
+        let (mut a, mut b) = (Rng::new(42), Rng::new(42));
+        for _ in 0..100 {
+            assert_eq!(a.next_u32(), b.next_u32());
+        }
+    }
+
+    #[test]
+    fn different_seeds_differ() {
+        let (mut a, mut b) = (Rng::new(1), Rng::new(2));
+        assert!((0..10).any(|_| a.next_u32() != b.next_u32()));
+    }
+
+    #[test]
+    fn zero_seed_does_not_get_stuck() {
+        let mut rng = Rng::new(0);
+        assert!((0..10).all(|_| rng.next_u32() != 0));
+    }
+
+    #[test]
+    fn below_stays_in_range() {
+        let mut rng = Rng::new(7);
+        assert!((0..1000).all(|_| rng.below(5) < 5));
+    }
+}
@@ -0,0 +1,1143 @@
+//! Game state and rules.
+
+use crate::alien::{formation, Alien, FormationMode, MAX_ALIENS};
+use crate::animation::Animation;
+use crate::boss::Boss;
+use crate::bunker::Bunkers;
+use crate::canvas::{Canvas, SIZE};
+use crate::config::Config;
+use crate::font::{draw_digit, draw_number};
+use crate::powerup::{Pickup, PowerUp};
+use crate::projectile::{Shots, MAX_SHOTS};
+use crate::rng::Rng;
+
+/// Seed of the random numbers until [GameState::set_seed] is called.
+const SEED: u32 = 0x2545_F491;
+
+/// Most power-ups falling at once.
+const MAX_PICKUPS: usize = 2;
+/// Most shield the ship can have.
+const MAX_SHIELD: u16 = 5;
+
+/// Score for destroying the boss.
+const BOSS_SCORE: u16 = 5;
+
+/// Ticks each number of the countdown is shown.
+const COUNTDOWN_STEP_TICKS: u16 = 10;
+/// Number the countdown starts from.
+const COUNTDOWN_FROM: u16 = 3;
+/// Ticks the victory signal is shown before the next wave.
+const ROUND_WON_TICKS: u16 = 30;
+/// Ticks the explosion is shown before the high score or title screen.
+const GAME_OVER_TICKS: u16 = 30;
+/// Ticks a new high score is shown before the title screen.
+const HIGH_SCORE_TICKS: u16 = 40;
+
+/// Something worth telling the player about, reported by [GameState::tick].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// The bullet hit an alien.
+    Hit { score: u16 },
+    /// The bullet missed the aliens and cost shield.
+    Miss { shield: u16 },
+    /// An alien shot hit the ship and cost shield.
+    ShipHit { shield: u16 },
+    /// The shield was gone and a new ship with `lives` left respawned.
+    LifeLost { lives: u16 },
+    /// The ship caught a power-up.
+    PickedUp { power_up: PowerUp },
+    /// A game begins after the countdown, playing the random numbers from `seed`.
+    GameStarted { seed: u32 },
+    /// The bullet hit the boss, which has `hp` hits left.
+    BossHit { hp: u16 },
+    /// The last hit destroyed the boss.
+    BossDefeated { score: u16 },
+    /// A wave begins after the countdown.
+    WaveStarted { wave: u16 },
+    /// All aliens, or the boss, of a wave are destroyed.
+    WaveCleared { wave: u16 },
+    Lost,
+    /// The game ended with more hits than any game before.
+    HighScore { score: u16 },
+}
+
+/// Where the game is in its flow.
+///
+/// The game moves through the phases like this:
+///
+/// ```text
+/// Title --fire--> Countdown --timer--> Playing <--pause/resume--> Paused
+///                     ^                 |    |
+///                     |                 |    +--last life gone/aliens landed--> GameOver --timer--> HighScore --timer--> Title
+///                     +--timer-- RoundWon <--wave cleared                             |                                    ^
+///                                                                                     +--timer, no new high score----------+
+/// ```
+///
+/// Restarting (SW0) goes to a fresh countdown from any phase. Fire skips the game over and high score screens.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    /// Waiting for the player to start a game.
+    Title,
+    /// Counting down to the start of a wave.
+    Countdown { ticks_left: u16 },
+    Playing,
+    /// Playing, but frozen until resumed.
+    Paused,
+    /// Showing the victory signal before the next wave.
+    RoundWon { ticks_left: u16 },
+    /// Showing the explosion of the ship.
+    GameOver { ticks_left: u16 },
+    /// Showing a new high score.
+    HighScore { ticks_left: u16 },
+}
+
+/// Everything the game remembers between ticks.
+#[derive(Clone, Debug)]
+pub struct GameState {
+    config: Config,
+    phase: Phase,
+    /// Alien pool, dead aliens are free slots.
+    aliens: [Alien; MAX_ALIENS],
+    /// The boss of a boss wave, [Boss::NONE] otherwise.
+    boss: Boss,
+    /// Bunkers stopping the shots of both sides, worn down over the whole game.
+    bunkers: Bunkers,
+    /// Ticks until the next step of a descending block.
+    step_cooldown: u16,
+    /// Shots of the aliens falling towards the ship.
+    alien_shots: Shots,
+    /// Ticks until the next alien shoots.
+    fire_cooldown: u16,
+    ship_x: usize,
+    ship_y: usize,
+    /// Shots of the player flying towards the aliens.
+    bullets: Shots,
+    /// Power-ups falling towards the ship.
+    pickups: [Option<Pickup>; MAX_PICKUPS],
+    /// The timed power-up in effect and the ticks it has left.
+    effect: Option<(PowerUp, u16)>,
+    /// Movement frequency (difficulty)
+    frequency: u16,
+    /// Hits in all waves of the current game
+    score: u16,
+    /// Player ship "shield" (0 costs a life, every miss reduces one)
+    shield: u16,
+    /// Ships left, including the one playing.
+    lives: u16,
+    /// Ticks the ship can't be hit after respawning.
+    invulnerable: u16,
+    /// Where the debris of the last hit alien is drawn, for one tick.
+    debris: Option<(usize, usize)>,
+    /// Number of the current wave, from 1.
+    wave: u16,
+    /// Best `score` since power on.
+    high_score: u16,
+    /// Ticks since power on, for blinking.
+    ticks: u16,
+    /// Source of the random spawn positions, alien movements and alien shots.
+    rng: Rng,
+    /// What `rng` starts from in the next game.
+    seed: u32,
+    /// What `rng` started from in the current game, `seed` may have changed since.
+    game_seed: u32,
+}
+
+impl GameState {
+    /// The game at power on with the default [Config], showing the title screen.
+    pub const fn new() -> Self {
+        GameState::with_config(Config::new())
+    }
+
+    /// The game at power on, showing the title screen.
+    pub const fn with_config(config: Config) -> Self {
+        GameState {
+            config,
+            phase: Phase::Title,
+            aliens: [Alien::DEAD; MAX_ALIENS],
+            boss: Boss::NONE,
+            bunkers: Bunkers::new(),
+            step_cooldown: config.descend_step_ticks,
+            alien_shots: Shots::new(),
+            fire_cooldown: config.alien_fire_ticks,
+            ship_x: 3,
+            ship_y: 7,
+            bullets: Shots::new(),
+            pickups: [None; MAX_PICKUPS],
+            effect: None,
+            frequency: 10,
+            score: 0,
+            shield: MAX_SHIELD,
+            lives: config.lives,
+            invulnerable: 0,
+            debris: None,
+            wave: 1,
+            high_score: 0,
+            ticks: 0,
+            rng: Rng::new(SEED),
+            seed: SEED,
+            game_seed: SEED,
+        }
+    }
+
+    /// Start a new game from the countdown.
+    /// The game speed, the high score and the seed are kept, they outlive one game.
+    /// The random numbers start over from the seed, so a game with the same seed and inputs plays the same.
+    pub fn init_game(&mut self) {
+        *self = GameState {
+            frequency: self.frequency,
+            high_score: self.high_score,
+            ticks: self.ticks,
+            rng: Rng::new(self.seed),
+            seed: self.seed,
+            game_seed: self.seed,
+            ..GameState::with_config(self.config)
+        };
+        self.spawn_wave();
+        self.start_countdown();
+    }
+
+    /// Set the seed of the random numbers for the games started from now on.
+    pub fn set_seed(&mut self, seed: u32) {
+        self.seed = seed;
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    /// The animation the phase shows, for playing it back faster than the game ticks can draw it.
+    /// [render](GameState::render) leaves the canvas blank in these phases.
+    pub fn animation(&self) -> Option<Animation> {
+        match self.phase {
+            Phase::RoundWon { .. } => Some(Animation::Victory),
+            Phase::GameOver { .. } => Some(Animation::Explosion),
+            _ => None,
+        }
+    }
+
+    /// Ships left while a game is on, none on the title and high score screens.
+    pub fn lives(&self) -> Option<u16> {
+        match self.phase {
+            Phase::Title | Phase::HighScore { .. } => None,
+            Phase::GameOver { .. } => Some(0),
+            _ => Some(self.lives),
+        }
+    }
+
+    /// Move ship right, but not over the edge.
+    pub fn move_right(&mut self) {
+        if self.phase == Phase::Playing && self.ship_x < 6 {
+            self.ship_x += 1;
+        }
+    }
+
+    /// Move ship left, but not over the edge.
+    pub fn move_left(&mut self) {
+        if self.phase == Phase::Playing && self.ship_x > 1 {
+            self.ship_x -= 1;
+        }
+    }
+
+    /// Shoot, unless `max_shots` bullets are already flying. Rapid fire lifts the limit to the whole pool, spread
+    /// shot fires three bullets side by side.
+    /// On the title, game over and high score screens this starts a new game instead.
+    pub fn shoot(&mut self) {
+        match self.phase {
+            Phase::Playing => {
+                let velocity = -(self.config.shot_speed as i8);
+                match self.effect {
+                    Some((PowerUp::RapidFire, _)) => {
+                        self.bullets.fire(MAX_SHOTS, self.ship_x, 6, velocity);
+                    }
+                    Some((PowerUp::SpreadShot, _)) => {
+                        self.bullets.fire(MAX_SHOTS, self.ship_x, 6, velocity);
+                        // Only the centre bullet costs shield on a miss, or the spread would cost more than it helps
+                        for x in [self.ship_x - 1, self.ship_x + 1] {
+                            if let Some(bullet) = self.bullets.fire(MAX_SHOTS, x, 6, velocity) {
+                                bullet.counts_miss = false;
+                            }
+                        }
+                    }
+                    _ => {
+                        self.bullets.fire(self.config.max_shots, self.ship_x, 6, velocity);
+                    }
+                }
+            }
+            Phase::GameOver { .. } => {
+                // Skipping the explosion must not lose a new high score
+                self.high_score = self.high_score.max(self.score);
+                self.init_game();
+            }
+            Phase::Title | Phase::HighScore { .. } => self.init_game(),
+            _ => {}
+        }
+    }
+
+    /// Pause a game being played, or resume a paused one.
+    pub fn toggle_pause(&mut self) {
+        match self.phase {
+            Phase::Playing => self.phase = Phase::Paused,
+            Phase::Paused => self.phase = Phase::Playing,
+            _ => {}
+        }
+    }
+
+    /// Swap game speed -> easy/hard difficulty level.
+    /// Returns the new frequency, for reprogramming the game timer.
+    pub fn swap_speed(&mut self) -> u16 {
+        if self.frequency == 10 {
+            self.frequency = 20;
+        } else {
+            self.frequency = 10;
+        }
+        self.frequency
+    }
+
+    /// Advance the game by one step of the game timer.
+    pub fn tick(&mut self, mut report: impl FnMut(Event)) {
+        self.ticks = self.ticks.wrapping_add(1);
+        self.debris = None;
+
+        match self.phase {
+            Phase::Title | Phase::Paused => {}
+            Phase::Countdown { ticks_left } => {
+                if ticks_left > 1 {
+                    self.phase = Phase::Countdown { ticks_left: ticks_left - 1 };
+                } else {
+                    self.phase = Phase::Playing;
+                    if self.wave == 1 {
+                        report(Event::GameStarted { seed: self.game_seed });
+                    }
+                    report(Event::WaveStarted { wave: self.wave });
+                }
+            }
+            Phase::Playing => {
+                self.invulnerable = self.invulnerable.saturating_sub(1);
+                self.handle_alien();
+                self.handle_bullet();
+                self.check_impact(&mut report);
+                if self.phase == Phase::Playing {
+                    self.handle_alien_shots(&mut report);
+                }
+                if self.phase == Phase::Playing {
+                    self.handle_pickups(&mut report);
+                }
+            }
+            Phase::RoundWon { ticks_left } => {
+                if ticks_left > 1 {
+                    self.phase = Phase::RoundWon { ticks_left: ticks_left - 1 };
+                } else {
+                    self.next_wave();
+                }
+            }
+            Phase::GameOver { ticks_left } => {
+                if ticks_left > 1 {
+                    self.phase = Phase::GameOver { ticks_left: ticks_left - 1 };
+                } else if self.score > self.high_score {
+                    self.high_score = self.score;
+                    self.phase = Phase::HighScore { ticks_left: HIGH_SCORE_TICKS };
+                    report(Event::HighScore { score: self.high_score });
+                } else {
+                    self.phase = Phase::Title;
+                }
+            }
+            Phase::HighScore { ticks_left } => {
+                if ticks_left > 1 {
+                    self.phase = Phase::HighScore { ticks_left: ticks_left - 1 };
+                } else {
+                    self.phase = Phase::Title;
+                }
+            }
+        }
+    }
+
+    fn start_countdown(&mut self) {
+        self.phase = Phase::Countdown {
+            ticks_left: COUNTDOWN_FROM * COUNTDOWN_STEP_TICKS,
+        };
+    }
+
+    /// Spawn the formation of the next wave, keeping the ship, the score and the shield.
+    fn next_wave(&mut self) {
+        self.wave += 1;
+        self.spawn_wave();
+        self.alien_shots.clear();
+        self.fire_cooldown = self.config.alien_fire_ticks;
+        self.step_cooldown = self.config.descend_step_ticks;
+        self.bullets.clear();
+        self.pickups = [None; MAX_PICKUPS];
+        self.start_countdown();
+    }
+
+    /// How the aliens of the current wave move.
+    fn formation_mode(&self) -> FormationMode {
+        #[allow(clippy::manual_is_multiple_of)] // `is_multiple_of` is too new for the board toolchain
+        let even = self.wave % 2 == 0;
+        if self.config.alternate_formation && even {
+            self.config.formation.other()
+        } else {
+            self.config.formation
+        }
+    }
+
+    /// Bring in the boss on every `boss_every`th wave, or else the alien formation of the wave.
+    fn spawn_wave(&mut self) {
+        let every = self.config.boss_every;
+        #[allow(clippy::manual_is_multiple_of)] // `is_multiple_of` is too new for the board toolchain
+        if every > 0 && self.wave % every == 0 {
+            self.aliens = [Alien::DEAD; MAX_ALIENS];
+            self.boss = Boss::new(self.config.boss_hp);
+        } else {
+            self.aliens = formation(self.wave, self.formation_mode(), &mut self.rng);
+            self.boss = Boss::NONE;
+        }
+    }
+
+    /// Checks if the bullets are hitting bunkers, aliens or the boss on their way this tick, and sets score/shield
+    /// accordingly. Each bullet stops at the first thing on its way.
+    /// A bullet reaching the top row without a hit is a miss, except for the side bullets of a spread.
+    /// Also checks for a cleared wave and the end of the game, which comes when the shield is gone or an alien
+    /// has come down to the ship.
+    pub fn check_impact(&mut self, report: &mut impl FnMut(Event)) {
+        for bullet in self.bullets.iter_mut() {
+            let hit = bullet.sweep().first_hit(|&(x, y)| {
+                self.bunkers.covers(x, y)
+                    || self.aliens.iter().any(|alien| alien.occupies(x, y))
+                    || self.boss.covers(x, y)
+            });
+            let Some((x, y)) = hit else {
+                if bullet.y == 0 {
+                    if bullet.counts_miss {
+                        self.shield = self.shield.saturating_sub(1); // miss
+                        report(Event::Miss { shield: self.shield });
+                    }
+                    bullet.active = false;
+                }
+                continue;
+            };
+
+            if self.bunkers.absorb(x, y) {
+                bullet.active = false;
+            } else if let Some(alien) = self.aliens.iter_mut().find(|alien| alien.occupies(x, y)) {
+                alien.alive = false;
+                let (x, y) = (alien.x, alien.y);
+                // Debris falls off on both sides of the alien after impact
+                self.debris = Some((x, y));
+
+                self.score += 1; // hit score plus
+                report(Event::Hit { score: self.score });
+                bullet.active = false;
+
+                if self.config.drop_chance > 0 && self.rng.one_in(self.config.drop_chance) {
+                    if let Some(slot) = self.pickups.iter_mut().find(|slot| slot.is_none()) {
+                        let power_up = PowerUp::random(&mut self.rng);
+                        *slot = Some(Pickup { x, y, power_up });
+                    }
+                }
+            } else if self.boss.covers(x, y) {
+                if self.boss.hit() {
+                    self.debris = Some((x, y));
+                    self.score += BOSS_SCORE;
+                    report(Event::BossDefeated { score: self.score });
+                } else {
+                    report(Event::BossHit { hp: self.boss.hp });
+                }
+                bullet.active = false;
+            }
+        }
+
+        if self.aliens.iter().all(|alien| !alien.alive) && !self.boss.alive() {
+            self.win_game(report);
+        } else if self.aliens_landed() {
+            // game over
+            self.lose_game(report);
+        } else if self.shield < 1 {
+            self.lose_life(report);
+        }
+    }
+
+    /// Moves the aliens as set by the [FormationMode] of the wave, and the boss.
+    pub fn handle_alien(&mut self) {
+        for alien in self.aliens.iter_mut() {
+            alien.prev = (alien.x, alien.y);
+        }
+        if self.boss.alive() {
+            self.boss.step();
+        }
+
+        match self.formation_mode() {
+            FormationMode::Bounce => {
+                for alien in self.aliens.iter_mut().filter(|alien| alien.alive) {
+                    alien.step(&mut self.rng, self.ship_x);
+                }
+            }
+            FormationMode::Descend => {
+                if self.step_cooldown > 1 {
+                    self.step_cooldown -= 1;
+                    return;
+                }
+                self.step_cooldown = self.config.descend_step_ticks;
+
+                // The block turns around and drops a row as soon as one of its aliens reaches an edge
+                let turn = self.aliens.iter().any(|alien| alien.alive && alien.at_edge());
+                for alien in self.aliens.iter_mut().filter(|alien| alien.alive) {
+                    if turn {
+                        alien.drop_row();
+                    } else {
+                        alien.advance();
+                    }
+                }
+            }
+        }
+    }
+
+    /// Whether an alien has come down to the row of the ship's top.
+    fn aliens_landed(&self) -> bool {
+        self.aliens.iter().any(|alien| alien.alive && alien.y >= self.ship_y - 1)
+    }
+
+    /// Moves the alien shots down by their speed and checks their way against the bunkers and the ship.
+    /// About every `alien_fire_ticks` a random living alien fires a new shot, or the boss fires from both legs.
+    pub fn handle_alien_shots(&mut self, report: &mut impl FnMut(Event)) {
+        let ship = self.ship_pixels();
+        let vulnerable = self.invulnerable == 0;
+        for shot in self.alien_shots.iter_mut() {
+            shot.step();
+            let hit = shot
+                .sweep()
+                .first_hit(|&(x, y)| self.bunkers.covers(x, y) || (vulnerable && ship.contains(&(x, y))));
+            if let Some((x, y)) = hit {
+                shot.active = false;
+                if !self.bunkers.absorb(x, y) {
+                    self.shield = self.shield.saturating_sub(1);
+                    report(Event::ShipHit { shield: self.shield });
+                }
+            } else if shot.y >= SIZE {
+                // Fell off the bottom
+                shot.active = false;
+            }
+        }
+        if self.shield < 1 {
+            self.lose_life(report);
+            return;
+        }
+
+        if self.fire_cooldown > 1 {
+            self.fire_cooldown -= 1;
+            return;
+        }
+        // Half to one and a half times the configured interval until the next shot
+        let ticks = usize::from(self.config.alien_fire_ticks);
+        self.fire_cooldown = (ticks / 2 + self.rng.below(ticks + 1)).max(1) as u16;
+
+        let velocity = self.config.alien_shot_speed as i8;
+        if self.boss.alive() {
+            for (x, y) in self.boss.guns() {
+                self.alien_shots.fire(MAX_SHOTS, x, y, velocity);
+            }
+            return;
+        }
+
+        let alive = self.aliens.iter().filter(|alien| alien.alive).count();
+        if alive == 0 {
+            return;
+        }
+        let shooter = self.aliens.iter().filter(|alien| alien.alive).nth(self.rng.below(alive));
+        if let Some(alien) = shooter {
+            self.alien_shots.fire(self.config.max_alien_shots, alien.x, alien.y + 1, velocity);
+        }
+    }
+
+    /// Runs down the timed power-up and lets the power-ups fall a row every other tick.
+    /// A power-up touching the ship takes effect, recharging the shield at once or replacing the timed effect.
+    pub fn handle_pickups(&mut self, report: &mut impl FnMut(Event)) {
+        self.effect = match self.effect {
+            Some((power_up, ticks_left)) if ticks_left > 1 => Some((power_up, ticks_left - 1)),
+            _ => None,
+        };
+
+        let ship = self.ship_pixels();
+        #[allow(clippy::manual_is_multiple_of)] // `is_multiple_of` is too new for the board toolchain
+        let fall = self.ticks % 2 == 0;
+        for slot in self.pickups.iter_mut() {
+            let Some(pickup) = slot else { continue };
+            if fall {
+                pickup.y += 1;
+            }
+            if ship.contains(&(pickup.x, pickup.y)) {
+                match pickup.power_up {
+                    PowerUp::ShieldRecharge => self.shield = (self.shield + 1).min(MAX_SHIELD),
+                    power_up => self.effect = Some((power_up, self.config.power_up_ticks)),
+                }
+                report(Event::PickedUp { power_up: pickup.power_up });
+                *slot = None;
+            } else if pickup.y >= SIZE {
+                *slot = None;
+            }
+        }
+    }
+
+    /// Moves the bullets up by their speed.
+    pub fn handle_bullet(&mut self) {
+        for bullet in self.bullets.iter_mut() {
+            bullet.step();
+        }
+    }
+
+    /// Set the wave to cleared, the next one starts after the victory signal.
+    pub fn win_game(&mut self, report: &mut impl FnMut(Event)) {
+        self.phase = Phase::RoundWon { ticks_left: ROUND_WON_TICKS };
+        report(Event::WaveCleared { wave: self.wave });
+    }
+
+    /// The shield is gone: respawn a ship in the middle with full shield, or lose the game if it was the last.
+    /// The new ship blinks while it can't be hit.
+    pub fn lose_life(&mut self, report: &mut impl FnMut(Event)) {
+        if self.lives <= 1 {
+            self.lives = 0;
+            self.lose_game(report);
+            return;
+        }
+        self.lives -= 1;
+        self.shield = MAX_SHIELD;
+        self.ship_x = 3;
+        self.invulnerable = self.config.invulnerable_ticks;
+        self.alien_shots.clear();
+        report(Event::LifeLost { lives: self.lives });
+    }
+
+    /// Set game to lost state.
+    pub fn lose_game(&mut self, report: &mut impl FnMut(Event)) {
+        self.phase = Phase::GameOver { ticks_left: GAME_OVER_TICKS };
+        report(Event::Lost);
+    }
+
+    /// Draw the whole picture of the current state.
+    /// Phases with an [animation](GameState::animation) leave the canvas blank, the animation takes the whole matrix.
+    pub fn render(&self, canvas: &mut impl Canvas) {
+        canvas.clear();
+
+        match self.phase {
+            Phase::Title => self.draw_title(canvas),
+            Phase::Countdown { ticks_left } => {
+                let number = (ticks_left - 1) / COUNTDOWN_STEP_TICKS + 1;
+                draw_digit(canvas, number as u8, 2, 1, (200, 200, 0));
+                self.draw_player_ship(canvas);
+            }
+            Phase::Playing => {
+                self.draw_score(canvas);
+                self.draw_playfield(canvas);
+            }
+            Phase::Paused => {
+                self.draw_score(canvas);
+                self.draw_playfield(canvas);
+                self.draw_pause(canvas);
+            }
+            // Only the animation of the phase is shown, played back from its own pages, see `animation`
+            Phase::RoundWon { .. } | Phase::GameOver { .. } => {}
+            Phase::HighScore { .. } => draw_number(canvas, self.high_score, (255, 180, 0)),
+        }
+    }
+
+    /// Aliens, boss, bunkers, bullets, shots, power-ups, ship and the debris of a hit.
+    fn draw_playfield(&self, canvas: &mut impl Canvas) {
+        for alien in self.aliens.iter().filter(|alien| alien.alive) {
+            canvas.set_pixel(alien.x, alien.y, 0, 255, 0);
+        }
+        self.boss.draw(canvas);
+        self.bunkers.draw(canvas);
+
+        for bullet in self.bullets.iter() {
+            canvas.set_pixel(bullet.x, bullet.y, 0, 0, 255);
+        }
+
+        for shot in self.alien_shots.iter() {
+            canvas.set_pixel(shot.x, shot.y, 255, 0, 255);
+        }
+
+        for pickup in self.pickups.iter().flatten() {
+            let (r, g, b) = pickup.power_up.color();
+            canvas.set_pixel(pickup.x, pickup.y, r, g, b);
+        }
+
+        self.draw_player_ship(canvas);
+
+        // Debris on both sides of a hit alien
+        if let Some((x, y)) = self.debris {
+            canvas.set_pixel(x + 1, y, 255, 255, 255);
+            if x > 0 {
+                canvas.set_pixel(x - 1, y, 255, 255, 255);
+            }
+        }
+    }
+
+    /// An alien over the ship, with a bullet blinking in between.
+    fn draw_title(&self, canvas: &mut impl Canvas) {
+        canvas.set_pixel(2, 1, 0, 255, 0);
+        canvas.set_pixel(3, 2, 0, 255, 0);
+        canvas.set_pixel(4, 2, 0, 255, 0);
+        canvas.set_pixel(5, 1, 0, 255, 0);
+        if self.ticks % 10 < 5 {
+            canvas.set_pixel(3, 4, 0, 0, 255);
+        }
+        self.draw_player_ship(canvas);
+    }
+
+    /// Two blinking bars over the frozen game.
+    fn draw_pause(&self, canvas: &mut impl Canvas) {
+        if self.ticks % 10 < 5 {
+            for y in 2..6 {
+                canvas.set_pixel(2, y, 255, 200, 0);
+                canvas.set_pixel(5, y, 255, 200, 0);
+            }
+        }
+    }
+
+    /// The pixels the player ship covers: center, left, right and top.
+    fn ship_pixels(&self) -> [(usize, usize); 4] {
+        [
+            (self.ship_x, self.ship_y),
+            (self.ship_x - 1, self.ship_y),
+            (self.ship_x + 1, self.ship_y),
+            (self.ship_x, self.ship_y - 1),
+        ]
+    }
+
+    /// Draws player ship, in the color of the power-up in effect and blinking while invulnerable.
+    fn draw_player_ship(&self, canvas: &mut impl Canvas) {
+        if self.invulnerable > 0 && self.ticks % 4 < 2 {
+            return;
+        }
+        let (r, g, b) = self.effect.map_or((255, 0, 0), |(power_up, _)| power_up.color());
+        for (x, y) in self.ship_pixels() {
+            canvas.set_pixel(x, y, r, g, b);
+        }
+    }
+
+    /// Draws dots for the aliens left in the wave & the shield lost.
+    /// Drawn first, the game graphics go over them.
+    fn draw_score(&self, canvas: &mut impl Canvas) {
+        let aliens_spot = 0;
+        let shield_spot = 7;
+        // Draw remaining alien dots
+        let aliens_left = self.aliens.iter().filter(|alien| alien.alive).count();
+        for i in (0..aliens_left).rev() {
+            canvas.set_pixel(aliens_spot, i, 200, 200, 200);
+        }
+        // Draw shield dots
+        for i in (0..6 - self.shield).rev() {
+            canvas.set_pixel(shield_spot, i.into(), 50, 100, 150);
+        }
+    }
+}
+
+impl Default for GameState {
+    fn default() -> Self {
+        GameState::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::bunker::BUNKER_ROW;
+    use crate::canvas::Framebuffer;
+    use std::vec::Vec;
+
+    /// Tick `ticks` times, returning the events reported.
+    fn run(game: &mut GameState, ticks: u16) -> Vec<Event> {
+        let mut events = Vec::new();
+        for _ in 0..ticks {
+            game.tick(|event| events.push(event));
+        }
+        events
+    }
+
+    /// A game whose countdown is over, so that its first wave is being played.
+    fn playing() -> GameState {
+        let mut game = GameState::new();
+        game.shoot();
+        run(&mut game, COUNTDOWN_FROM * COUNTDOWN_STEP_TICKS);
+        game
+    }
+
+    #[test]
+    fn fire_on_the_title_screen_starts_the_countdown() {
+        let mut game = GameState::new();
+        assert_eq!(game.phase(), Phase::Title);
+        game.shoot();
+        assert!(matches!(game.phase(), Phase::Countdown { .. }));
+    }
+
+    #[test]
+    fn countdown_ends_in_the_first_wave() {
+        let mut game = GameState::new();
+        game.set_seed(1234);
+        game.shoot();
+        let events = run(&mut game, COUNTDOWN_FROM * COUNTDOWN_STEP_TICKS);
+        assert_eq!(game.phase(), Phase::Playing);
+        assert_eq!(events, [Event::GameStarted { seed: 1234 }, Event::WaveStarted { wave: 1 }]);
+    }
+
+    #[test]
+    fn game_started_reports_the_seed_the_game_plays() {
+        let mut game = GameState::new();
+        game.set_seed(1);
+        game.shoot();
+        // A fire press during the countdown only changes the seed of the next game
+        game.set_seed(2);
+        game.shoot();
+        let events = run(&mut game, COUNTDOWN_FROM * COUNTDOWN_STEP_TICKS);
+        assert_eq!(events[0], Event::GameStarted { seed: 1 });
+    }
+
+    #[test]
+    fn same_seed_plays_the_same() {
+        let mut a = GameState::new();
+        let mut b = GameState::new();
+        for game in [&mut a, &mut b] {
+            game.set_seed(77);
+            game.shoot();
+        }
+        assert_eq!(run(&mut a, 200), run(&mut b, 200));
+    }
+
+    #[test]
+    fn render_draws_the_ship_and_the_aliens() {
+        let game = playing();
+        let mut canvas = Framebuffer::new();
+        game.render(&mut canvas);
+        for (x, y) in game.ship_pixels() {
+            assert_ne!(canvas.pixel(x, y), (0, 0, 0));
+        }
+        for alien in game.aliens.iter().filter(|alien| alien.alive) {
+            assert_ne!(canvas.pixel(alien.x, alien.y), (0, 0, 0));
+        }
+    }
+
+    #[test]
+    fn pause_freezes_the_game_until_resumed() {
+        let mut game = playing();
+        game.toggle_pause();
+        assert_eq!(game.phase(), Phase::Paused);
+        let aliens = game.aliens;
+        run(&mut game, 10);
+        assert_eq!(game.aliens, aliens);
+        game.toggle_pause();
+        assert_eq!(game.phase(), Phase::Playing);
+    }
+
+    #[test]
+    fn clearing_a_wave_leads_to_the_next_one() {
+        let mut game = playing();
+        game.aliens = [Alien::DEAD; MAX_ALIENS];
+        assert_eq!(run(&mut game, 1), [Event::WaveCleared { wave: 1 }]);
+        assert!(matches!(game.phase(), Phase::RoundWon { .. }));
+        run(&mut game, ROUND_WON_TICKS);
+        assert!(matches!(game.phase(), Phase::Countdown { .. }));
+        assert_eq!(game.wave, 2);
+    }
+
+    /// `game` with only a shot coming down at the top of the ship, and no new shots for a while.
+    fn shot_at_the_ship(mut game: GameState) -> GameState {
+        game.invulnerable = 0;
+        game.fire_cooldown = 100;
+        game.alien_shots.clear();
+        game.alien_shots.fire(1, game.ship_x, game.ship_y - 2, 1);
+        game
+    }
+
+    #[test]
+    fn alien_shot_hitting_the_ship_takes_shield() {
+        let mut game = shot_at_the_ship(playing());
+        let mut events = Vec::new();
+        game.handle_alien_shots(&mut |event| events.push(event));
+        assert_eq!(events, [Event::ShipHit { shield: MAX_SHIELD - 1 }]);
+        assert_eq!(game.alien_shots.iter().count(), 0);
+    }
+
+    #[test]
+    fn alien_shot_taking_the_last_shield_costs_a_life() {
+        let mut game = playing();
+        game.shield = 1;
+        let mut game = shot_at_the_ship(game);
+        let mut events = Vec::new();
+        game.handle_alien_shots(&mut |event| events.push(event));
+        let lives = game.config.lives - 1;
+        assert_eq!(events, [Event::ShipHit { shield: 0 }, Event::LifeLost { lives }]);
+        assert_eq!((game.lives, game.shield), (lives, MAX_SHIELD));
+    }
+
+    #[test]
+    fn invulnerable_ship_lets_alien_shots_through() {
+        let mut game = shot_at_the_ship(playing());
+        game.invulnerable = 5;
+        let mut events = Vec::new();
+        game.handle_alien_shots(&mut |event| events.push(event));
+        assert_eq!(events, []);
+        assert_eq!(game.shield, MAX_SHIELD);
+    }
+
+    #[test]
+    fn losing_without_a_record_goes_back_to_the_title() {
+        let mut game = playing();
+        game.lose_game(&mut |_| {});
+        assert!(matches!(game.phase(), Phase::GameOver { .. }));
+        assert_eq!(game.lives(), Some(0));
+        run(&mut game, GAME_OVER_TICKS);
+        assert_eq!(game.phase(), Phase::Title);
+    }
+
+    #[test]
+    fn losing_with_a_record_shows_the_high_score() {
+        let mut game = playing();
+        game.score = 3;
+        game.lose_game(&mut |_| {});
+        let events = run(&mut game, GAME_OVER_TICKS);
+        assert_eq!(events, [Event::HighScore { score: 3 }]);
+        assert!(matches!(game.phase(), Phase::HighScore { .. }));
+        run(&mut game, HIGH_SCORE_TICKS);
+        assert_eq!(game.phase(), Phase::Title);
+    }
+
+    #[test]
+    fn skipping_the_game_over_screen_keeps_the_high_score() {
+        let mut game = playing();
+        game.score = 3;
+        game.lose_game(&mut |_| {});
+        game.shoot();
+        assert!(matches!(game.phase(), Phase::Countdown { .. }));
+        assert_eq!(game.high_score, 3);
+    }
+
+    /// A game being played with a single alien at (`x`, `y`) that was at `prev` before its last step.
+    fn one_alien(config: Config, x: usize, y: usize, prev: (usize, usize)) -> GameState {
+        let mut game = GameState::with_config(config);
+        game.init_game();
+        game.phase = Phase::Playing;
+        game.aliens = [Alien::DEAD; MAX_ALIENS];
+        game.aliens[0] = Alien {
+            x,
+            y,
+            prev,
+            row: y,
+            ..Alien::DEAD
+        };
+        game.aliens[0].alive = true;
+        game
+    }
+
+    /// A game with the aliens moving as a [FormationMode::Descend] block, a step every tick.
+    fn descending(x: usize, y: usize) -> GameState {
+        let config = Config {
+            formation: FormationMode::Descend,
+            alternate_formation: false,
+            descend_step_ticks: 1,
+            ..Config::new()
+        };
+        one_alien(config, x, y, (x, y))
+    }
+
+    #[test]
+    fn every_other_wave_moves_the_other_way() {
+        let mut game = GameState::new();
+        assert_eq!(game.formation_mode(), FormationMode::Bounce);
+        game.wave = 2;
+        assert_eq!(game.formation_mode(), FormationMode::Descend);
+    }
+
+    #[test]
+    fn block_advances_until_it_hits_an_edge() {
+        let mut game = descending(5, 1);
+        game.aliens[1] = Alien { x: 3, ..game.aliens[0] };
+        game.handle_alien();
+        assert_eq!([(game.aliens[0].x, game.aliens[0].y), (game.aliens[1].x, game.aliens[1].y)], [(6, 1), (4, 1)]);
+    }
+
+    #[test]
+    fn block_drops_a_row_and_turns_at_an_edge() {
+        let mut game = descending(7, 1);
+        game.aliens[1] = Alien { x: 3, ..game.aliens[0] };
+        game.handle_alien();
+        for alien in &game.aliens[..2] {
+            assert_eq!((alien.y, alien.row, alien.increment), (2, 2, -1));
+        }
+        assert_eq!((game.aliens[0].x, game.aliens[1].x), (7, 3));
+    }
+
+    #[test]
+    fn landed_block_ends_the_game() {
+        // At the edge two rows above the ship, the next step drops it next to the top of the ship
+        let mut game = descending(7, GameState::new().ship_y - 2);
+        let events = run(&mut game, 1);
+        assert_eq!(events, [Event::Lost]);
+        assert!(matches!(game.phase(), Phase::GameOver { .. }));
+    }
+
+    #[test]
+    fn fast_bullet_hits_the_alien_it_passes() {
+        let config = Config {
+            shot_speed: 3,
+            ..Config::new()
+        };
+        let mut game = one_alien(config, 3, 4, (3, 4));
+        game.shoot();
+        game.handle_bullet();
+        // The bullet jumped from row 6 over the alien to row 3
+        assert_eq!(game.bullets.iter().next().map(|bullet| bullet.y), Some(3));
+        let mut events = Vec::new();
+        game.check_impact(&mut |event| events.push(event));
+        assert_eq!(events[0], Event::Hit { score: 1 });
+        assert!(!game.aliens[0].alive);
+    }
+
+    #[test]
+    fn bullet_and_alien_swapping_places_hit() {
+        let mut game = one_alien(Config::new(), 3, 4, (3, 3));
+        game.bullets.fire(1, 3, 4, -1);
+        game.handle_bullet();
+        // The alien came down from row 3 to 4 while the bullet went up from 4 to 3
+        let mut events = Vec::new();
+        game.check_impact(&mut |event| events.push(event));
+        assert_eq!(events[0], Event::Hit { score: 1 });
+        assert!(!game.aliens[0].alive);
+    }
+
+    #[test]
+    fn spread_shot_misses_cost_one_shield() {
+        let mut game = one_alien(Config::new(), 0, 0, (0, 0));
+        game.effect = Some((PowerUp::SpreadShot, 100));
+        game.shoot();
+        assert_eq!(game.bullets.iter().count(), 3);
+        for bullet in game.bullets.iter_mut() {
+            (bullet.y, bullet.from_y) = (0, 0);
+        }
+        let mut events = Vec::new();
+        game.check_impact(&mut |event| events.push(event));
+        assert_eq!(events, [Event::Miss { shield: MAX_SHIELD - 1 }]);
+    }
+
+    #[test]
+    fn no_drops_with_a_drop_chance_of_zero() {
+        let config = Config {
+            drop_chance: 0,
+            ..Config::new()
+        };
+        let mut game = one_alien(config, 3, 4, (3, 4));
+        game.bullets.fire(1, 3, 5, -1);
+        game.handle_bullet();
+        game.check_impact(&mut |_| {});
+        assert!(game.pickups.iter().all(Option::is_none));
+    }
+
+    /// [Bunkers::new] after a hit on the bunker pixel in column `x`.
+    fn bunkers_hit_at(x: usize) -> Bunkers {
+        let mut bunkers = Bunkers::new();
+        bunkers.absorb(x, BUNKER_ROW);
+        bunkers
+    }
+
+    #[test]
+    fn bunker_stops_a_bullet_and_takes_a_hit() {
+        let mut game = one_alien(Config::new(), 7, 0, (7, 0));
+        game.bullets.fire(1, 1, BUNKER_ROW + 1, -1);
+        game.handle_bullet();
+        let mut events = Vec::new();
+        game.check_impact(&mut |event| events.push(event));
+        assert_eq!(events, []);
+        assert_eq!(game.bullets.iter().count(), 0);
+        assert_eq!(game.bunkers, bunkers_hit_at(1));
+    }
+
+    #[test]
+    fn bunker_stops_an_alien_shot_and_takes_a_hit() {
+        let mut game = one_alien(Config::new(), 7, 0, (7, 0));
+        game.fire_cooldown = 100;
+        game.alien_shots.fire(1, 1, BUNKER_ROW - 1, 1);
+        let mut events = Vec::new();
+        game.handle_alien_shots(&mut |event| events.push(event));
+        assert_eq!(events, []);
+        assert_eq!(game.alien_shots.iter().count(), 0);
+        assert_eq!(game.bunkers, bunkers_hit_at(1));
+    }
+
+    #[test]
+    fn worn_out_bunker_lets_bullets_through() {
+        let mut game = one_alien(Config::new(), 7, 0, (7, 0));
+        while game.bunkers.absorb(1, BUNKER_ROW) {}
+        game.bullets.fire(1, 1, BUNKER_ROW + 1, -1);
+        game.handle_bullet();
+        game.check_impact(&mut |_| {});
+        assert_eq!(game.bullets.iter().map(|bullet| bullet.y).next(), Some(BUNKER_ROW));
+    }
+
+    /// A game being played on a boss wave, against a boss with `hp` hit points.
+    fn boss_wave(hp: u16) -> GameState {
+        let config = Config {
+            boss_every: 1,
+            boss_hp: hp,
+            ..Config::new()
+        };
+        let mut game = GameState::with_config(config);
+        game.init_game();
+        game.spawn_wave();
+        game.phase = Phase::Playing;
+        game
+    }
+
+    #[test]
+    fn boss_fires_from_both_guns() {
+        let mut game = boss_wave(3);
+        game.fire_cooldown = 1;
+        game.handle_alien_shots(&mut |_| {});
+        let shots: Vec<_> = game.alien_shots.iter().map(|shot| (shot.x, shot.y)).collect();
+        assert_eq!(shots, game.boss.guns());
+    }
+
+    #[test]
+    fn bullet_takes_a_hit_point_off_the_boss() {
+        let mut game = boss_wave(3);
+        let (x, y) = game.boss.guns()[0];
+        game.bullets.fire(1, x, y, -1);
+        game.handle_bullet();
+        let mut events = Vec::new();
+        game.check_impact(&mut |event| events.push(event));
+        assert_eq!(events, [Event::BossHit { hp: 2 }]);
+        assert_eq!(game.bullets.iter().count(), 0);
+    }
+
+    #[test]
+    fn defeating_the_boss_leads_to_the_next_wave() {
+        let mut game = boss_wave(1);
+        let (x, y) = game.boss.guns()[0];
+        game.bullets.fire(1, x, y, -1);
+        game.handle_bullet();
+        let mut events = Vec::new();
+        game.check_impact(&mut |event| events.push(event));
+        assert_eq!(events, [Event::BossDefeated { score: BOSS_SCORE }, Event::WaveCleared { wave: 1 }]);
+        run(&mut game, ROUND_WON_TICKS);
+        assert!(matches!(game.phase(), Phase::Countdown { .. }));
+        assert_eq!(game.wave, 2);
+    }
+
+    #[test]
+    fn last_life_lost_ends_the_game() {
+        let mut game = playing();
+        game.lives = 1;
+        game.shield = 0;
+        game.lose_life(&mut |_| {});
+        assert!(matches!(game.phase(), Phase::GameOver { .. }));
+    }
+
+    #[test]
+    fn life_lost_respawns_with_full_shield() {
+        let mut game = playing();
+        game.shield = 0;
+        let mut events = Vec::new();
+        game.lose_life(&mut |event| events.push(event));
+        assert_eq!(events, [Event::LifeLost { lives: game.config.lives - 1 }]);
+        assert_eq!((game.phase(), game.shield), (Phase::Playing, MAX_SHIELD));
+    }
+}
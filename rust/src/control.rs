@@ -0,0 +1,122 @@
+//! Typed access to the control register of the Colors Shield.
+//!
+//! The register drives the serial interface of the DM163 LED driver.
+//! Each signal has its own bit:
+//!
+//! | Bit | Signal | Meaning                                                   |
+//! |-----|--------|-----------------------------------------------------------|
+//! | 0   | RST    | DM163 reset, active low                                   |
+//! | 1   | LAT    | Latch, moves the shifted data to the outputs              |
+//! | 2   | SB     | Bank select, 0 = 6-bit dot correction, 1 = 8-bit PWM      |
+//! | 3   | SCK    | Serial clock, data is shifted in on the rising edge       |
+//! | 4   | SDA    | Serial data                                               |
+//!
+//! [Control] only offers operations named after these signals, so that the protocol reads as signal changes
+//! and no unrelated bit can be flipped by accident.
+//!
+//! The register is never read back: [Control] keeps a shadow copy of the value last written, so every signal
+//! change is a single write.
+
+use core::sync::atomic::{AtomicU8, Ordering};
+
+use crate::register::Register;
+use crate::xil::usleep;
+
+/// Bit of the reset signal.
+pub const RST: u8 = 1 << 0;
+/// Bit of the latch signal.
+pub const LAT: u8 = 1 << 1;
+/// Bit of the bank select signal.
+pub const SB: u8 = 1 << 2;
+/// Bit of the serial clock.
+pub const SCK: u8 = 1 << 3;
+/// Bit of the serial data.
+pub const SDA: u8 = 1 << 4;
+
+/// Register bank of the DM163 that the serial data goes to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bank {
+    /// 6 bits per channel, scales the brightness of each channel.
+    DotCorrection,
+    /// 8 bits per channel, the brightness of the pixel.
+    Pwm,
+}
+
+/// The control register of the Colors Shield.
+pub struct Control<R> {
+    register: R,
+    /// The value last written to `register`.
+    shadow: AtomicU8,
+}
+
+impl<R: Register<u8>> Control<R> {
+    pub const fn new(register: R) -> Self {
+        Control {
+            register,
+            shadow: AtomicU8::new(0),
+        }
+    }
+
+    /// Reset the DM163 and leave all signals low, except RST which is released.
+    pub fn reset(&self) {
+        self.write(0); // RST low, everything else clear
+        unsafe {
+            usleep(500);
+        }
+        self.write(RST); // Back from reset
+        unsafe {
+            usleep(500);
+        }
+    }
+
+    /// Set the serial data line.
+    pub fn set_sda(&self, high: bool) {
+        self.set(SDA, high);
+    }
+
+    /// Clock up and down, shifting in the bit on SDA.
+    pub fn pulse_sck(&self) {
+        self.set(SCK, true);
+        self.set(SCK, false);
+    }
+
+    /// Shift in `bytes`, most significant bit first.
+    /// Takes two writes per bit, against three with `set_sda` and `pulse_sck`, and leaves SCK low.
+    pub fn shift_out(&self, bytes: &[u8]) {
+        let idle = self.shadow.load(Ordering::Relaxed) & !(SDA | SCK);
+        let mut data = idle;
+        for &byte in bytes {
+            for k in (0..8).rev() {
+                data = if byte >> k & 1 != 0 { idle | SDA } else { idle };
+                self.register.write(data);
+                self.register.write(data | SCK);
+            }
+        }
+        self.write(data);
+    }
+
+    /// Latch signal up and down.
+    pub fn pulse_lat(&self) {
+        self.set(LAT, true);
+        self.set(LAT, false);
+    }
+
+    /// Select the register bank that the following data is shifted into.
+    pub fn select_bank(&self, bank: Bank) {
+        self.set(SB, bank == Bank::Pwm);
+    }
+
+    fn set(&self, signal: u8, high: bool) {
+        let value = self.shadow.load(Ordering::Relaxed);
+        if high {
+            self.write(value | signal);
+        } else {
+            self.write(value & !signal);
+        }
+    }
+
+    fn write(&self, value: u8) {
+        self.shadow.store(value, Ordering::Relaxed);
+        self.register.write(value);
+    }
+}
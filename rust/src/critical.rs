@@ -0,0 +1,70 @@
+//! A cell for data shared between the main program and the interrupt handlers.
+//!
+//! The Cortex-A9 runs one thing at a time, so the only way two handlers can touch the same data at once is an
+//! interrupt arriving in the middle of an access. [IrqCell] rules that out by masking interrupts with
+//! `Xil_ExceptionDisable` for the duration of each access.
+
+use core::cell::{Cell, UnsafeCell};
+
+use crate::xil;
+
+/// Holds a value that is only reachable with interrupts disabled, see [IrqCell::lock].
+pub struct IrqCell<T> {
+    value: UnsafeCell<T>,
+    locked: Cell<bool>,
+}
+
+// Accesses are serialized by masking interrupts, and the board has a single core.
+unsafe impl<T> Sync for IrqCell<T> {}
+
+impl<T> IrqCell<T> {
+    pub const fn new(value: T) -> Self {
+        IrqCell {
+            value: UnsafeCell::new(value),
+            locked: Cell::new(false),
+        }
+    }
+
+    /// Run `f` on the value with interrupts disabled.
+    ///
+    /// Interrupts are enabled again afterwards only if they were enabled before, so this is safe to call both from
+    /// the main program and from inside an interrupt handler.
+    ///
+    /// # Panics
+    ///
+    /// If called again for the same cell from inside `f`.
+    pub fn lock<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        let enabled = interrupts_enabled();
+        unsafe {
+            xil::Xil_ExceptionDisable();
+        }
+
+        assert!(!self.locked.replace(true), "IrqCell locked twice");
+        // Interrupts are off and the flag is set, so this is the only reference to the value.
+        let result = f(unsafe { &mut *self.value.get() });
+        self.locked.set(false);
+
+        if enabled {
+            unsafe {
+                xil::Xil_ExceptionEnable();
+            }
+        }
+        result
+    }
+}
+
+/// Whether IRQs are currently unmasked, i.e. the I bit of CPSR is clear.
+#[cfg(not(feature = "sim"))]
+fn interrupts_enabled() -> bool {
+    let cpsr: u32;
+    unsafe {
+        core::arch::asm!("mrs {}, cpsr", out(reg) cpsr);
+    }
+    cpsr & (1 << 7) == 0
+}
+
+/// The simulator calls the handlers one after another, there is nothing to mask.
+#[cfg(feature = "sim")]
+fn interrupts_enabled() -> bool {
+    true
+}
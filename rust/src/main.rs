@@ -5,64 +5,96 @@
 // Do not include Rust standard library.
 // Rust standard library is not available for bare metal Cortex-A9.
 // Thus we use [core](https://doc.rust-lang.org/core/)-library.
-#![no_std]
+// The host simulator (`--features sim`) runs on Linux and uses the standard library instead.
+#![cfg_attr(not(feature = "sim"), no_std)]
 // Open feature gates to certain, currently WIP, features which might become part of Rust in future.
-#![feature(start)]
+#![cfg_attr(not(feature = "sim"), feature(start))]
 
 // Define crate's module hierarchy.
+mod control;
+mod critical;
+#[cfg(not(feature = "sim"))]
 mod interrupt;
 mod pixel;
+#[cfg(not(feature = "sim"))]
 mod print;
+mod register;
+#[cfg(feature = "sim")]
+mod sim;
 
 // `xil_sys` contains the Xilinx Cortex-A9 board support package (BSP) and a Rust FFI.
 // We rename the module here as `xil`.
+#[cfg(not(feature = "sim"))]
 use xil_sys as xil;
 
+// In the simulator build the BSP and the interrupt setup are host stand-ins.
+#[cfg(feature = "sim")]
+use sim::{interrupt, new_seed, xil};
+
+use control::Control;
+use critical::IrqCell;
+use register::Register;
+
+// The game rules and graphics live in the hardware-independent `game` crate.
+use game::{Animation, Canvas, Event, GameState, PowerUp};
+
 // Re-import symbols from pixel without the `pixel::` prefix.
 use pixel::*;
 
 // Rust `core` imports for using C-style void-pointers and info for a custom panic implementation.
-use core::{ffi::c_void, panic::PanicInfo};
+use core::ffi::c_void;
+#[cfg(not(feature = "sim"))]
+use core::panic::PanicInfo;
 
 // Frequency change is used for game difficulty adjusting
 use crate::interrupt::change_freq;
+// and for the time slots of the matrix refresh.
+#[cfg(feature = "sim")]
+use crate::interrupt::set_slot_interval;
 
 // Declare static globals like in the C-version.
 // This is a reasonable way of communicating between threads in interrupt-driven concurrency.
 pub static mut A_GLOBAL: usize = 0;
 
+// Peripheral registers are volatile memory-mapped I/O on the board and recording mocks in the simulator.
+#[cfg(not(feature = "sim"))]
+type Reg<T> = register::Mmio<T>;
+#[cfg(feature = "sim")]
+type Reg<T> = register::Mock<T>;
+
 // Define the address of the ordinary LED interface in physical memory.
 // Putting bits into the LED address the right way may cause desired blinking of hardware LEDs.
-// FIXME: 0x00000000 is not the LED address.
-// The correct address can be found in some of the provided documentation.
-pub const LED_ADDRESS: *mut u8 = 0x41200000 as *mut u8;
+pub static LED_ADDRESS: Reg<u8> = unsafe { Reg::new(0x41200000) };
 
 // Define other addresses
-pub const CHANNEL: *mut u8 = 0x41220000 as *mut u8;
-pub const CONTROL: *mut u8 = 0x41220008 as *mut u8;
-pub const INPUTS: *mut u32 = 0xE000A068 as *mut u32;
-pub const RGB: *mut u8 = 0x41240000 as *mut u8;
-
-// Game variables
+// The Colors Shield addresses are named, the simulator's model of the shield needs them too.
+pub const CHANNEL_ADDRESS: usize = 0x41220000;
+pub const CONTROL_ADDRESS: usize = 0x41220008;
+pub static CHANNEL: Reg<u8> = unsafe { Reg::new(CHANNEL_ADDRESS) };
+pub static CONTROL: Control<Reg<u8>> = Control::new(unsafe { Reg::new(CONTROL_ADDRESS) });
+pub static INPUTS: Reg<u32> = unsafe { Reg::new(0xE000A068) };
+pub static RGB: Reg<u8> = unsafe { Reg::new(0x41240000) };
+// Lower word of the free-running Cortex-A9 global timer, started by the BSP for `usleep`.
+#[cfg(not(feature = "sim"))]
+pub static GLOBAL_TIMER: Reg<u32> = unsafe { Reg::new(0xF8F00200) };
+
+// Rate of the refresh interrupt (`tick_handler`) set up by `interrupt::init`, and the matrix lines it scans a second.
+// `tick_handler` reprograms its timer to show each line in `SLOTS` calls of different lengths.
+pub const REFRESH_FREQUENCY: u32 = 800;
+
+// Line of the LED matrix refreshed next and its slot, only touched by `tick_handler`.
 pub static mut OPEN_CHANNEL: usize = 0;
-pub static mut ALIEN_X: usize = 0;
-pub static mut ALIEN_Y: usize = 0;
-pub static mut SHIP_X: usize = 3;
-pub static mut SHIP_Y: usize = 7;
-pub static mut BULLET_X: usize = 0;
-pub static mut BULLET_Y: usize = 0;
-pub static mut INCREMENT: i8 = 1;
-pub static mut FREQUENCY: u16 = 10;
-pub static mut SCORE: u16 = 0;
-pub static mut SHIELD: u16 = 5;
-pub static mut IS_WON: u16 = 0; // Flag for game won
-pub static mut IS_LOST: u16 = 0; // flag for game lose
-pub static BULLET_HAVEN: usize = 50; // "hiding place" for bullet when off board
+pub static mut OPEN_SLOT: usize = 0;
+
+// Game variables, shared by the interrupt handlers.
+static GAME: IrqCell<GameState> = IrqCell::new(GameState::new());
 
 // Assembly stuff for blinking leds
+#[cfg(not(feature = "sim"))]
 use core::arch::global_asm;
 //global_asm!(include_str!("blinker.S"));
 
+#[cfg(not(feature = "sim"))]
 global_asm!("
 .data
 	dir: .byte 1		//8-bit variable for direction
@@ -105,13 +137,19 @@ storing:
 	bx lr					//Return to place where this function has been called.
 ");
 
+#[cfg(not(feature = "sim"))]
 extern "C" {
     fn blinker() -> u32;
 }
 
+// The simulator can't run ARM assembly, so it provides a Rust port of the blinker.
+#[cfg(feature = "sim")]
+use sim::blinker;
+
 // The #[start] attribute tell's the cross-compiler where to start executing.
 // Normally it is not needed.
 // UnderSCORE before the argument signals that the parameter is not used.
+#[cfg(not(feature = "sim"))]
 #[start]
 fn main(_argc: isize, _argv: *const *const u8) -> isize {
     // Initialize board interrupt functions.
@@ -120,13 +158,13 @@ fn main(_argc: isize, _argv: *const *const u8) -> isize {
     
     note("Game init started.");
 
-    // Init game variables
-    unsafe {
-        init_game();
-    }
+    // Show the title screen until a game is started
+    show_title();
     
     setup_led_matrix();
 
+    prerender_animations();
+
     // An unsafe block for setting up the LED-matrix using the C-API, and for touching a static global.
     unsafe {
         // Setting a static global variable requires an `unsafe` block in Rust.
@@ -152,6 +190,27 @@ fn main(_argc: isize, _argv: *const *const u8) -> isize {
     loop {}
 }
 
+/// Entry point of the host simulator build.
+///
+/// Does the same initialization as the board `main`, but instead of idling while the hardware raises interrupts,
+/// hands control to the simulator loop, which calls the interrupt handlers itself.
+#[cfg(feature = "sim")]
+fn main() {
+    interrupt::init();
+
+    note("Game init started.");
+
+    show_title();
+
+    setup_led_matrix();
+
+    prerender_animations();
+
+    note("Rust application initialized!");
+
+    sim::run();
+}
+
 /// Interrupt handler for switches and buttons.
 ///
 /// Pressing a button or switching a switch causes an GPIO interrupt.
@@ -176,63 +235,39 @@ pub unsafe extern "C" fn button_handler(callback_ref: *mut c_void, _bank: u32, s
     // decimal 4). You can use binary, decimal or hex for the match, but I
     // found the binary representation more readable.
     
-    // Reset ship graphics
-    set_pixel(SHIP_X, SHIP_Y,0,0,0); // center
-    set_pixel(SHIP_X-1, SHIP_Y,0,0,0); // left
-    set_pixel(SHIP_X+1, SHIP_Y,0,0,0); // right
-    set_pixel(SHIP_X, SHIP_Y-1,0,0,0); // top
-
     //Hint: Status==0x01 ->btn0, Status==0x02->btn1, Status==0x04->btn2, Status==0x08-> btn3, Status==0x10->SW0, Status==0x20 -> SW1
-    match status {
-        // No buttons are pressed
-        0b000000 => {},
-        // TODO: match into a pattern here
-        //If true, btn0 was used to trigger interrupt
-        0x01 => {
-            // Move ship right
-            SHIP_X += 1;
-        }
-        0x02 => {
-
-        }
-        0x04 => {
-            // Shoot
-            if BULLET_Y > 10 { // number rolls over 0 to max
-                BULLET_X = SHIP_X;
-                BULLET_Y = 6;
+    let new_frequency = GAME.lock(|game| {
+        match status {
+            // No buttons are pressed
+            0b000000 => {},
+            //If true, btn0 was used to trigger interrupt
+            0x01 => game.move_right(),
+            // Pause or resume, the display keeps refreshing meanwhile
+            0x02 => game.toggle_pause(),
+            // Shoot, or start a game on the title screen
+            0x04 => {
+                game.set_seed(new_seed());
+                game.shoot();
             }
-        }
-        0x08 => {
-            // Move ship left
-            SHIP_X -= 1;
-        }
-        0x10 => {
+            0x08 => game.move_left(),
             // Restart game
-            init_game(); 
-        }
-        0x20 => {
-            // Swap game speed -> easy/hard difficulty level    
-            if FREQUENCY == 10 {
-                FREQUENCY = 20;
-            } else {
-                FREQUENCY = 10;
+            0x10 => {
+                game.set_seed(new_seed());
+                game.init_game();
+                note("New game initialized.");
             }
-            change_freq(FREQUENCY.into());
-            note("Changed game speed.");
+            // Swap game speed -> easy/hard difficulty level
+            0x20 => return Some(game.swap_speed()),
+            // `_` is the 'rest' pattern, that is handled if no other variant matches above
+            _ => {},
         }
-        // `_` is the 'rest' pattern, that is handled if no other variant matches above
-        _ => {},
-    }
-
+        draw(game);
+        None
+    });
 
-    // Prevent ship from moving over edges to right
-    if SHIP_X > 6 {
-        SHIP_X = 6;
-    }
-
-    // Prevent ship from moving over edges to left
-    if SHIP_X < 1 {
-        SHIP_X = 1;
+    if let Some(frequency) = new_frequency {
+        change_freq(frequency.into());
+        note("Changed game speed.");
     }
 
     // End of your code
@@ -249,17 +284,31 @@ pub unsafe extern "C" fn tick_handler(callback_ref: *mut c_void) {
     if OPEN_CHANNEL > 7 {
 		OPEN_CHANNEL = 0;
 	}
-	open_line(99);
-	run(OPEN_CHANNEL);
-	open_line(OPEN_CHANNEL.try_into().unwrap());
-	OPEN_CHANNEL+=1;
-    // End of your code
-
     // Cast `void*` received from the C API to the "Triple Timer Counter" (TTC) instance pointer.
     // The C API needs to use void pointers to pass data around.
     // The C specification does not describe abstract data types (ADT).
     let ttc = callback_ref as *mut xil::XTtcPs;
 
+    if OPEN_CHANNEL == 0 && OPEN_SLOT == 0 {
+        start_refresh();
+    }
+    // The line stays on through its slots, only the data of the DM163 changes
+    if OPEN_SLOT == 0 {
+        open_line(99);
+    }
+    run(OPEN_CHANNEL, OPEN_SLOT);
+    if OPEN_SLOT == 0 {
+        open_line(OPEN_CHANNEL.try_into().unwrap());
+    }
+    // The next interrupt comes when the slot is over
+    set_slot_interval(ttc, OPEN_SLOT);
+    OPEN_SLOT += 1;
+    if OPEN_SLOT == SLOTS {
+        OPEN_SLOT = 0;
+        OPEN_CHANNEL += 1;
+    }
+    // End of your code
+
     // Clear timer interrupt status.
     let status_event = xil::XTtcPs_GetInterruptStatus(ttc);
     xil::XTtcPs_ClearInterruptStatus(ttc, status_event);
@@ -270,28 +319,28 @@ pub unsafe extern "C" fn tick_handler(callback_ref: *mut c_void) {
 /// See also [tick_handler](fn.tick_handler.html) and its line comments for details.
 pub unsafe extern "C" fn tick_handler_1(callback_ref: *mut c_void) {
     // TODO: Write code here
-    // If the game is still running, update game graphics etc.    
-    
-    if IS_WON != 1 && IS_LOST == 0 {
-        
-		// Alien handling (draw & check movement)
-        handle_alien();
-        
-        // Handle bullet (draw & check movement)
-        handle_bullet();
-        
-        // Draw player ship
-		draw_player_ship();
-        
-		// Bullet impact!?
-		check_impact();
-	}
-	
-    // Draw SCORE & SHIELD
-	draw_score();    
+    // If the game is still running, move aliens & shots and check for impact, then draw everything.
+    let lives = GAME.lock(|game| {
+        game.tick(report);
+        draw(game);
+        // The pre-rendered animation of the phase plays from the refresh interrupt, in place of the frames drawn here
+        play(game.animation().map(playback));
+        game.lives()
+    });
+
+    match lives {
+        // During a game the LEDs show the lives left, one LED each
+        Some(lives) => LED_ADDRESS.write(((1u16 << lives.min(4)) - 1) as u8),
+        // Call Assembly blinker function
+        None => {
+            blinker();
+        }
+    }
 
-    // Call Assembly blinker function
-    blinker();
+    // Now and then report how much of the scan-out could reuse the line already in the DM163
+    if let Some(lines) = take_lines_report() {
+        println64!("Lines sent/s: {} of {}", lines, REFRESH_FREQUENCY * SLOTS as u32);
+    }
 
     // End of your code
 
@@ -302,6 +351,7 @@ pub unsafe extern "C" fn tick_handler_1(callback_ref: *mut c_void) {
 }
 
 /// A custom panic handler for Cortex-A9.
+#[cfg(not(feature = "sim"))]
 #[panic_handler]
 fn panic(info: &PanicInfo) -> ! {
     // logs "panicked at '$reason', src/main.rs:27:4" to host stdout
@@ -315,175 +365,144 @@ pub fn note(note: &str) {
     println64!("Note: {}", note);
 }
 
-unsafe fn init_game() {
-	OPEN_CHANNEL = 0; // Currently open channel
-	ALIEN_X = 0; // Alien X coordinate
-	ALIEN_Y = 0; // Alien Y coordinate
-	SHIP_X = 3; // Ship center X coordinate
-	SHIP_Y = 7; // Ship center Y coordinate
-	BULLET_X = 0; // Bullet X coordinate
-	BULLET_Y = BULLET_HAVEN; // Bullet Y coordinate
-	INCREMENT = 1; // Current direction of alien movement
-	FREQUENCY = 10; // Movement frequency (difficulty)
-	SCORE = 0; // Game SCORE
-	SHIELD = 5; // Player ship "SHIELD" (0 is game over, every miss reduces one)
-	IS_WON = 0; // flag for game won
-	IS_LOST = 0; // flag for game lost
-
-	// Clear screen
-    for i in 0..8 {
-		for j in 0..8 {
-			set_pixel(i,j,0,0,0);
-		}
-	}
-    
-    note("New game initialized.");
-}
+/// The LED matrix, as the game sees it.
+struct Matrix;
 
-// Checks if bullet is hitting alien, and sets SCORE/SHIELD accordingly
-// Also checks SCORE and SHIELD for game end
-unsafe fn check_impact() {
-    if BULLET_Y == 0 {
-        if ALIEN_X == BULLET_X {
-            // Draw pixels on both side of the alien to indicate debris falling off after impact
-            set_pixel(BULLET_X+1,BULLET_Y,255,255,255);
-            set_pixel(BULLET_X-1,BULLET_Y,255,255,255);
-            
-            SCORE += 1; // hit score plus
-            
-            println64!("Score now: {}", SCORE);
-            
-            if SCORE > 2 {
-                // game win
-                win_game();
-            }
-        } else {
-            SHIELD -= 1; // miss
-            println64!("Shield now: {}", SHIELD);
-            if SHIELD < 1 {
-                // game over
-                lose_game();
-            }
+impl Canvas for Matrix {
+    fn set_pixel(&mut self, x: usize, y: usize, r: u8, g: u8, b: u8) {
+        // Drawing goes to the back page, which the refresh interrupt doesn't read.
+        unsafe {
+            pixel::set_pixel(x, y, r, g, b);
         }
-        BULLET_Y = BULLET_HAVEN; // "Hide" bullet from matrix
     }
 }
 
-// Draws player ship
-// Note: ship old dots are reset when buttons are pressed
-unsafe fn draw_player_ship() {
-    set_pixel(SHIP_X, SHIP_Y,255,0,0); // center
-    set_pixel(SHIP_X-1, SHIP_Y,255,0,0); // left
-    set_pixel(SHIP_X+1, SHIP_Y,255,0,0); // right
-    set_pixel(SHIP_X, SHIP_Y-1,255,0,0); // top
+/// A page of the LED matrix, for drawing frames ahead of time.
+struct Page(usize);
+
+impl Canvas for Page {
+    fn set_pixel(&mut self, x: usize, y: usize, r: u8, g: u8, b: u8) {
+        unsafe {
+            pixel::set_page_pixel(self.0, x, y, r, g, b);
+        }
+    }
 }
 
-// Resets alien graphics, checks its movement direction and draws it to a new spot
-unsafe fn handle_alien() {
-    set_pixel(ALIEN_X,ALIEN_Y,0,0,0); // clear old alien location
+/// The animations kept in pages, each taking a page per frame.
+const ANIMATIONS: [Animation; 2] = [Animation::Victory, Animation::Explosion];
 
-    // Check if running to edges
-    if ALIEN_X < 1 {
-        INCREMENT = 1;
-    }
-    if ALIEN_X >= 7 {
-        INCREMENT = -1;
-    }
+/// First page of the frames of `animation`.
+fn first_page(animation: Animation) -> usize {
+    let before = ANIMATIONS.iter().take_while(|&&other| other != animation);
+    FREE_PAGES.start + before.map(|other| other.frames()).sum::<usize>()
+}
 
-    // Move alien
-    if INCREMENT > 0 {
-        ALIEN_X += 1;
-    } else {
-        ALIEN_X -= 1;
+/// Draw every frame of the animations into its page, ready to be played.
+fn prerender_animations() {
+    // The frames of all animations have to fit in the pages not used for drawing
+    const _: () = {
+        let (mut frames, mut i) = (0, 0);
+        while i < ANIMATIONS.len() {
+            frames += ANIMATIONS[i].frames();
+            i += 1;
+        }
+        assert!(frames <= FREE_PAGES.end - FREE_PAGES.start, "the animations don't fit in FREE_PAGES");
+    };
+
+    for animation in ANIMATIONS {
+        for frame in 0..animation.frames() {
+            let mut page = Page(first_page(animation) + frame);
+            page.clear();
+            animation.draw_frame(&mut page, frame);
+        }
     }
-
-    // Draw alien
-    set_pixel(ALIEN_X,ALIEN_Y,0,255,0);
 }
 
-//
-unsafe fn handle_bullet() {
-    // Draw bullet if on matrix
-    if BULLET_Y < 10{
-        set_pixel(BULLET_X,BULLET_Y,0,0,0); // bullet old location clear
+/// How the refresh interrupt plays the frames of `animation`.
+fn playback(animation: Animation) -> Playback {
+    Playback {
+        first: first_page(animation),
+        frames: animation.frames(),
+        // A full refresh scans the eight lines, `REFRESH_FREQUENCY` lines a second
+        refreshes_per_frame: REFRESH_FREQUENCY / 8 / animation.fps(),
+    }
+}
 
-        // Draw bullet (if on screen)
-        if BULLET_Y > 0 && BULLET_Y < 10 {
-            BULLET_Y -= 1;
+/// Draw the whole frame of the game and show it at once, from the next refresh of the matrix.
+/// The frame is left out while the last one still waits to be shown, the next call draws the game as it is then.
+fn draw(game: &GameState) {
+    unsafe {
+        if pixel::flip_pending() {
+            return;
         }
-        set_pixel(BULLET_X, BULLET_Y, 0,0,255);
+    }
+    game.render(&mut Matrix);
+    unsafe {
+        pixel::flip();
     }
 }
 
-// Draws SCORE & SHIELD dots
-unsafe fn draw_score() {
-	let score_spot = 0;
-	let shield_spot = 7;
-	// Clear old SCORE dots
-    for i in (6..0).rev() {
-		set_pixel(score_spot,usize::try_from(i).unwrap(),0,0,0);
-	}
-	// Draw SCORE dots
-	for i in (0..6-SCORE).rev() {
-		set_pixel(score_spot,usize::try_from(i).unwrap(),200,200,200);
-	}
-	// Clear old SHIELD
-	for i in (1..6).rev() {
-			set_pixel(shield_spot,usize::try_from(i).unwrap(),0,0,0);
-		}
-	// Draw SHIELD dots
-	for i in (0..6-SHIELD).rev() {
-		set_pixel(shield_spot,usize::try_from(i).unwrap(),50,100,150);
-	}
+/// Draw the title screen the game starts in.
+fn show_title() {
+    GAME.lock(|game| draw(game));
 }
 
-// Set game to win state
-// Draw win effect
-unsafe fn win_game() {
-    note("Game won!");
-	IS_WON = 1;
-	let r = 0;
-	let g = 200;
-	let b = 100;
-	let mut x = 0;
-    let mut y = 0;
-
-    // Draw pixels to form a victory signal
-	set_pixel(4,3,r,g,b);
-	set_pixel(5,1,r,g,b);
-	set_pixel(4,4,r,g,b);
-	set_pixel(5,2,r,g,b);
-	set_pixel(1,2,r,g,b);
-	set_pixel(2,4,r,g,b);
-    note("Winning pixels drawn.");
+/// Seed for the random numbers of a new game.
+/// The global timer runs at hundreds of MHz, so the moment of the button press makes every game different.
+#[cfg(not(feature = "sim"))]
+fn new_seed() -> u32 {
+    GLOBAL_TIMER.read()
 }
 
-// Set game to lost state
-// Draw lose effect
-unsafe fn lose_game() {
-    note("Game lost!");
-	let mut r = 1;
-	let mut g = 1;
-	let mut b = 1;
-	IS_LOST = 1;
-    // Draw some colours to LED matrix to represent player ship "explosion"
-	for i in 0..7 {
-		for j in 0..7 {
-			r+=2;
-			g+=4;
-			b+=6;
-            // Prevent colour overflow
-            if r > 240 {
-                r = 0;
-            }
-            if g > 240 {
-                g = 0;
-            }
-            if b > 240 {
-                b = 0;
-            }
-			set_pixel(i,j,r,g,b);
-		}
-        open_line(i.try_into().unwrap());
-	}
-}
\ No newline at end of file
+/// Interval register values of the refresh timer for each slot, worked out by the first `set_slot_interval`.
+#[cfg(not(feature = "sim"))]
+static mut SLOT_INTERVALS: Option<[u32; SLOTS]> = None;
+
+/// Let the refresh timer `ttc` interrupt next when slot `slot` is over, reprogramming it like `change_freq` does
+/// the game timer.
+/// Only the first call works out the timing, the ones after it just write the interval register.
+#[cfg(not(feature = "sim"))]
+unsafe fn set_slot_interval(ttc: *mut xil::XTtcPs, slot: usize) {
+    // Offset of the interval register of a TTC timer
+    const INTERVAL_OFFSET: usize = 0x24;
+
+    let intervals = match SLOT_INTERVALS {
+        Some(intervals) => intervals,
+        None => {
+            // The longest slot sets the prescaler, the shorter ones count fewer ticks of the same clock
+            let longest = SLOT_WEIGHTS.iter().copied().max().unwrap_or(1);
+            let mut interval: xil::XInterval = 0;
+            let mut prescaler = 0;
+            xil::XTtcPs_CalcIntervalFromFreq(ttc, REFRESH_FREQUENCY * 4 / longest, &mut interval, &mut prescaler);
+            xil::XTtcPs_SetPrescaler(ttc, prescaler);
+            let intervals = SLOT_WEIGHTS.map(|weight| u32::from(interval) * weight / longest);
+            SLOT_INTERVALS = Some(intervals);
+            intervals
+        }
+    };
+    // `XTtcPs_SetInterval` is a C macro, so it is done here with a register write
+    Reg::<u32>::new((*ttc).Config.BaseAddress as usize + INTERVAL_OFFSET).write(intervals[slot]);
+}
+
+/// Log game events over UART.
+/// The seed is logged so that a game can be replayed, e.g. in the simulator with `--seed`.
+fn report(event: Event) {
+    match event {
+        Event::Hit { score } => println64!("Score now: {}", score),
+        Event::Miss { shield } => println64!("Shield now: {}", shield),
+        Event::ShipHit { shield } => println64!("Ship hit! Shield now: {}", shield),
+        Event::LifeLost { lives } => println64!("Ship lost! Lives left: {}", lives),
+        Event::BossHit { hp } => println64!("Boss hit! {} hits to go", hp),
+        Event::BossDefeated { score } => println64!("Boss defeated! Score now: {}", score),
+        Event::PickedUp { power_up } => match power_up {
+            PowerUp::RapidFire => note("Rapid fire!"),
+            PowerUp::SpreadShot => note("Spread shot!"),
+            PowerUp::ShieldRecharge => note("Shield recharged!"),
+        },
+        Event::GameStarted { seed } => println64!("Game started with seed {}", seed),
+        Event::WaveStarted { wave } => println64!("Wave {} started.", wave),
+        Event::WaveCleared { wave } => println64!("Wave {} cleared!", wave),
+        Event::Lost => note("Game lost!"),
+        Event::HighScore { score } => println64!("New high score: {}", score),
+    }
+}
@@ -1,138 +1,288 @@
 /* Some tips.
 
-# How to set memory address's content to zero?
+# How to set register's content to zero?
 
-core::ptr::write_volatile(ADDRESS, 0);
+REGISTER.write(0);
 
-# How to read memory address's content?
+# How to read register's content?
 
-let value = core::ptr::read_volatile(ADDRESS);
+let value = REGISTER.read();
 */
 
-use crate::{xil, CHANNEL, CONTROL, RGB, note};
+use crate::control::Bank;
+use crate::register::Register;
+use crate::{xil, CHANNEL, CONTROL, REFRESH_FREQUENCY, RGB};
 
 use xil::{usleep};
 
 const PAGE_SIZE: usize = 10;
 
+/// The front page, which `run` scans out to the matrix.
 pub static mut PAGE: usize = 0;
 
-/// Table for dots.
-/// Indices are page, x, y, color.
+/// The back page, which `set_pixel` draws into until `flip` shows it.
+static mut BACK_PAGE: usize = 1;
+
+/// Pages not used by the double buffer, free for pre-rendered frames.
+pub const FREE_PAGES: core::ops::Range<usize> = 2..PAGE_SIZE;
+
+/// A sequence of pages shown one after another in a loop.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Playback {
+    /// Page of the first frame, the others follow it.
+    pub first: usize,
+    pub frames: usize,
+    /// Full refreshes of the matrix each frame is shown for.
+    pub refreshes_per_frame: u32,
+}
+
+/// The sequence the refresh interrupt shows instead of the front page, if any.
+static mut PLAYBACK: Option<Playback> = None;
+/// Frame of `PLAYBACK` being shown.
+static mut PLAYBACK_FRAME: usize = 0;
+/// Full refreshes the current frame has been shown for.
+static mut PLAYBACK_REFRESHES: u32 = 0;
+/// The sequence asked for with `play`, which takes over `PLAYBACK` when the next refresh starts.
+static mut NEXT_PLAYBACK: Option<Playback> = None;
+
+/// Whether `flip` was asked for and waits for the next refresh to start.
+static mut FLIP_PENDING: bool = false;
+
+/// Time slots each line is shown in, one refresh interrupt each.
+/// Every slot shows the upper 8 bits of the 10-bit brightness from `GAMMA`, the two lower bits add one more step
+/// to the slots weighted like the bit, so that the average over the line comes out at the full 10 bits.
+pub const SLOTS: usize = 3;
+/// How long each slot lasts, in quarters of the time of a line.
+pub const SLOT_WEIGHTS: [u32; SLOTS] = [1, 1, 2];
+
+/// 10-bit brightness of each 8-bit color value, with a gamma of 2.2, so that even steps of the color values look
+/// like even steps on the LEDs.
+static GAMMA: [u16; 256] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 2, 2,
+    2, 3, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 9, 9, 10,
+    11, 11, 12, 13, 14, 15, 16, 16, 17, 18, 19, 20, 21, 23, 24, 25,
+    26, 27, 28, 30, 31, 32, 34, 35, 36, 38, 39, 41, 42, 44, 46, 47,
+    49, 51, 52, 54, 56, 58, 60, 61, 63, 65, 67, 69, 71, 73, 76, 78,
+    80, 82, 84, 87, 89, 91, 94, 96, 98, 101, 103, 106, 109, 111, 114, 117,
+    119, 122, 125, 128, 130, 133, 136, 139, 142, 145, 148, 151, 155, 158, 161, 164,
+    167, 171, 174, 177, 181, 184, 188, 191, 195, 198, 202, 206, 209, 213, 217, 221,
+    225, 228, 232, 236, 240, 244, 248, 252, 257, 261, 265, 269, 274, 278, 282, 287,
+    291, 295, 300, 304, 309, 314, 318, 323, 328, 333, 337, 342, 347, 352, 357, 362,
+    367, 372, 377, 382, 387, 393, 398, 403, 408, 414, 419, 425, 430, 436, 441, 447,
+    452, 458, 464, 470, 475, 481, 487, 493, 499, 505, 511, 517, 523, 529, 535, 542,
+    548, 554, 561, 567, 573, 580, 586, 593, 599, 606, 613, 619, 626, 633, 640, 647,
+    653, 660, 667, 674, 681, 689, 696, 703, 710, 717, 725, 732, 739, 747, 754, 762,
+    769, 777, 784, 792, 800, 807, 815, 823, 831, 839, 847, 855, 863, 871, 879, 887,
+    895, 903, 912, 920, 928, 937, 945, 954, 962, 971, 979, 988, 997, 1005, 1014, 1023,
+];
+
+/// The value the DM163 shows for `value` in each slot.
+fn slot_values(value: u8) -> [u8; SLOTS] {
+    let brightness = GAMMA[usize::from(value)];
+    let upper = (brightness >> 2) as u8;
+    [
+        upper.saturating_add((brightness & 1) as u8),
+        upper,
+        upper.saturating_add((brightness >> 1 & 1) as u8),
+    ]
+}
+
+/// Bytes of one line: eight pixels of three colors.
+const LINE_BYTES: usize = 24;
+
+/// Table for dots, as the DM163 shows them.
+/// Indices are page, line (x), slot and byte. The bytes of a line are in the order `run` shifts them out: pixel (y)
+/// after pixel, with the colors of each pixel as blue, green, red.
 /// Initialized to zero.
-static mut DOTS: [[[[u8; PAGE_SIZE]; 8]; 8]; 3] = [[[[0; PAGE_SIZE]; 8]; 8]; 3];
+static mut DOTS: [[[[u8; LINE_BYTES]; SLOTS]; 8]; PAGE_SIZE] = [[[[0; LINE_BYTES]; SLOTS]; 8]; PAGE_SIZE];
+
+/// The line held by the outputs of the DM163, unknown until `run` has latched one.
+/// A line that is the same needs no shifting, the outputs keep showing it while the row driver moves on.
+static mut LATCHED: Option<[u8; LINE_BYTES]> = None;
+
+/// Slot of the line held by the outputs of the DM163.
+#[cfg(feature = "sim")]
+static mut LATCHED_SLOT: usize = 0;
+
+/// Lines shifted out and line slots scanned so far in the current second.
+static mut LINES_SENT: u32 = 0;
+static mut LINES_SCANNED: u32 = 0;
+/// Lines shifted out during the last full second, one for each slot sent.
+static mut LINES_SENT_PER_SECOND: u32 = 0;
+/// Seconds between two reports of [take_lines_report].
+const LINES_REPORT_SECONDS: u32 = 60;
+/// Full seconds since the last report, and whether the next one is due.
+static mut SECONDS_UNREPORTED: u32 = 0;
+static mut LINES_REPORT_DUE: bool = false;
 
 /// TODO: does this function have to be unsafe?
 pub fn setup_led_matrix() {
     // The screen must be reset at start.
-    // Tip: use the following one-liners to flip bits on or off at ADDRESS.
-    // Oh yes, it's a zero-cost lambda function in an embedded application.
-    /*
-    mutate_ptr(ADDR, |x| x | 1);
-    mutate_ptr(ADDR, |x| x ^ 1);
-    */
-
-    // TODO: Write code that sets 6-bit values in register of DM163 chip.
-    // It is recommended that every bit in that register is set to 1.
-    // 6-bits and 24 "bytes", so some kind of loop structure could be nice
-
-    //reseting screen at start is a MUST to operation (Set RST-pin to 1).
+    // Reseting screen at start is a MUST to operation (RST-pin ends up at 1).
+    CHANNEL.write(0);
+    CONTROL.reset();
 
-    // Basic inits
+    // Set the 6-bit dot correction values of DM163 chip.
+    // This goes through the shift register, so the next line has to be shifted out again.
     unsafe {
-        core::ptr::write_volatile(CHANNEL,0);
-        core::ptr::write_volatile(CONTROL,0); // Control signals all clear
-        usleep(500); // Does this actually even work?
-        core::ptr::write_volatile(CONTROL,0b00001); // Rst signal
-        usleep(500);
-        core::ptr::write_volatile(CONTROL, 0); // Back from reset
-        usleep(500);
-        core::ptr::write_volatile(CONTROL, 0b00001); // Rst signal final up
-        mutate_ptr(CONTROL, |x| x | 5); // Activate SDA - serial input bit
+        LATCHED = None;
     }
-
-    
-    // Init bits on and off
+    // Every bit in that register is set to 1, 6-bits for 24 channels.
+    CONTROL.select_bank(Bank::DotCorrection);
+    CONTROL.set_sda(true);
 	for _i in 0..6 {
 		for _j in 0..24 {
-            unsafe {
-                // Clock up and down
-                mutate_ptr(CONTROL, |x| x | 4);
-                mutate_ptr(CONTROL, |x| x ^ 4);
-            }
+            CONTROL.pulse_sck();
 		}
 	}
+    CONTROL.set_sda(false);
     
     // Just make some lights to see setup is happening
-    unsafe {    
-        
-        core::ptr::write_volatile(RGB, 0b011011); // Just a remark that something has started
+    RGB.write(0b011011); // Just a remark that something has started
+    unsafe {
         usleep(500);
-        core::ptr::write_volatile(RGB, 0); // Turn off debug toy RGB LEDs
-
     }
+    RGB.write(0); // Turn off debug toy RGB LEDs
 
-    //Final thing in this function is to set SB-bit to 1 to enable transmission to 8-bit register.
-    unsafe {        
-        mutate_ptr(CONTROL, |x| x | 3);     
-    }
+    // Final thing in this function is to set SB to select the 8-bit register for the pixel data.
+    // This also loads the dot correction values.
+    CONTROL.select_bank(Bank::Pwm);
 }
 
 /// Set the value of one pixel at the LED matrix.
+/// The pixel is drawn into the back page, it shows after the next `flip`.
 /// Function is unsafe because it uses global memory.
+/// Coordinates outside of the 8x8 matrix are ignored.
 /// TODO: does this function have to be unsafe?
 pub unsafe fn set_pixel(x: usize, y: usize, r: u8, g: u8, b: u8) {
     // TODO: Set new pixel value.
     // Take the parameeters and put them into the DOTS array.
-    
-    DOTS[0][x][y][PAGE]=b;
-    DOTS[1][x][y][PAGE]=g;
-    DOTS[2][x][y][PAGE]=r;
+    set_page_pixel(BACK_PAGE, x, y, r, g, b);
+}
+
+/// Set the value of one pixel in page `page`, e.g. to draw a frame for a `Playback`.
+/// Coordinates outside of the 8x8 matrix are ignored.
+pub unsafe fn set_page_pixel(page: usize, x: usize, y: usize, r: u8, g: u8, b: u8) {
+    if x > 7 || y > 7 {
+        return;
+    }
+
+    let (r, g, b) = (slot_values(r), slot_values(g), slot_values(b));
+    for slot in 0..SLOTS {
+        DOTS[page][x][slot][y * 3..y * 3 + 3].copy_from_slice(&[b[slot], g[slot], r[slot]]);
+    }
+}
+
+/// Start looping the pages of `playback` in place of the front page, or go back to the front page with `None`,
+/// from the next refresh on. Asking for the sequence already playing lets it go on where it is.
+pub unsafe fn play(playback: Option<Playback>) {
+    NEXT_PLAYBACK = playback;
+}
+
+/// Start a full refresh of the matrix: do the flip and the change of playback asked for since the last one, or
+/// else count the refresh towards the frame of the playback, moving to the next frame when its time is up.
+/// Called by the refresh interrupt before it starts on the first line, so that every refresh scans one frame.
+pub unsafe fn start_refresh() {
+    if FLIP_PENDING {
+        core::ptr::swap(core::ptr::addr_of_mut!(PAGE), core::ptr::addr_of_mut!(BACK_PAGE));
+        FLIP_PENDING = false;
+    }
+
+    if PLAYBACK != NEXT_PLAYBACK {
+        PLAYBACK = NEXT_PLAYBACK;
+        PLAYBACK_FRAME = 0;
+        PLAYBACK_REFRESHES = 0;
+    } else if let Some(playback) = PLAYBACK {
+        PLAYBACK_REFRESHES += 1;
+        if PLAYBACK_REFRESHES >= playback.refreshes_per_frame {
+            PLAYBACK_REFRESHES = 0;
+            PLAYBACK_FRAME = (PLAYBACK_FRAME + 1) % playback.frames;
+        }
+    }
+}
+
+/// The page the matrix shows: the frame of the playback, or else the front page.
+unsafe fn shown_page() -> usize {
+    match PLAYBACK {
+        Some(playback) => playback.first + PLAYBACK_FRAME,
+        None => PAGE,
+    }
+}
+
+/// Show the finished frame of the back page from the next refresh on, after which the next frame is drawn into
+/// the page shown so far. Nothing may be drawn until then, see `flip_pending`.
+pub unsafe fn flip() {
+    FLIP_PENDING = true;
+}
+
+/// Whether the back page is still waiting to be shown, and so can't be drawn into yet.
+pub unsafe fn flip_pending() -> bool {
+    FLIP_PENDING
+}
+
+/// Read back the value the DM163 should show for one pixel of the page shown as `(r, g, b)`, in the slot latched
+/// last. Used by the simulator to check the matrix.
+#[cfg(feature = "sim")]
+pub unsafe fn get_pixel(x: usize, y: usize) -> (u8, u8, u8) {
+    let dot = &DOTS[shown_page()][x][LATCHED_SLOT][y * 3..y * 3 + 3];
+    (dot[2], dot[1], dot[0])
 }
 
 /// Refresh new data into the LED matrix.
 /// Hint: This function is supposed to send 24-bytes and parameter x is for x-coordinate.
+/// Sends slot `slot` of line `c`, which is to be shown for `SLOT_WEIGHTS[slot]` quarters of the time of a line.
+/// The bytes of the line are shifted out straight from `DOTS`, in the order they are stored.
+/// A line the same as the one latched last is not sent at all.
 /// TODO: does this function have to be unsafe?
-pub unsafe fn run(c: usize) {
-    // TODO: Write into the LED matrix driver (8-bit data).
-    // Use values from DOTS array.
-
-    // Set latch 0
-    let mut bitvalue: u8= core::ptr::read_volatile(CONTROL);
-    bitvalue = bitvalue & !(1u8 << 2);
-    core::ptr::write_volatile(CONTROL, bitvalue);
-
-    // Iterate the channel pixels
-    for i in 0..8 { // channel level
-    	for j in 0..3 { // led level
-            let mut dot = DOTS[j as usize][c as usize][i as usize][PAGE as usize];
-    		for k in 0..8 { // bit level
-				if dot & 0b10000000 != 0 {
-                    mutate_ptr(CONTROL, |x| x | 5); // SDA 1
-				} else {
-                    // Set SDA to 0
-                    mutate_ptr(CONTROL, |x| x ^ 5); // SDA 0
-				}
-                mutate_ptr(CONTROL, |x| x | 4); // Clock up
-                mutate_ptr(CONTROL, |x| x ^ 4); // Clock down
-				dot <<= 1;
-			}
-		}
-	}
-	latch();
-	// clock should go 0
-    let mut clockvalue: u8 = core::ptr::read_volatile(CONTROL);
-    clockvalue = clockvalue & !(1u8 << 4);
-    core::ptr::write_volatile(CONTROL, clockvalue);
+pub unsafe fn run(c: usize, slot: usize) {
+    // The whole line comes from the same page
+    let page = shown_page();
+
+    if LATCHED != Some(DOTS[page][c][slot]) {
+        CONTROL.shift_out(&*core::ptr::addr_of!(DOTS[page][c][slot]));
+        latch();
+        LATCHED = Some(DOTS[page][c][slot]);
+        LINES_SENT += 1;
+    }
+    #[cfg(feature = "sim")]
+    {
+        LATCHED_SLOT = slot;
+    }
+
+    // The refresh interrupt scans `REFRESH_FREQUENCY` lines a second, in `SLOTS` interrupts each
+    LINES_SCANNED += 1;
+    if LINES_SCANNED >= REFRESH_FREQUENCY * SLOTS as u32 {
+        LINES_SENT_PER_SECOND = LINES_SENT;
+        LINES_SENT = 0;
+        LINES_SCANNED = 0;
+        SECONDS_UNREPORTED += 1;
+        if SECONDS_UNREPORTED >= LINES_REPORT_SECONDS {
+            SECONDS_UNREPORTED = 0;
+            LINES_REPORT_DUE = true;
+        }
+    }
+}
+
+/// Lines shifted out to the DM163 during the last second, out of the `REFRESH_FREQUENCY` times `SLOTS` scanned.
+pub unsafe fn lines_sent_per_second() -> u32 {
+    LINES_SENT_PER_SECOND
+}
+
+/// [lines_sent_per_second], once every `LINES_REPORT_SECONDS` seconds, for logging it from outside the refresh
+/// interrupt.
+pub unsafe fn take_lines_report() -> Option<u32> {
+    if !LINES_REPORT_DUE {
+        return None;
+    }
+    LINES_REPORT_DUE = false;
+    Some(lines_sent_per_second())
 }
 
 /// Latch signal for the colors shield.
 /// See `colorsshield.pdf` for how latching works.
 /// TODO: does this function have to be unsafe?
 unsafe fn latch() {
-    // Latch signal up and down
-    mutate_ptr(CONTROL, |x| x | 2);
-    mutate_ptr(CONTROL, |x| x ^ 2);
+    CONTROL.pulse_lat();
 }
 
 /// Set one channel as active.
@@ -148,58 +298,120 @@ pub unsafe fn open_line(i: u8) {
 
     match i {
 		0 => {
-            core::ptr::write_volatile(CHANNEL, 0b00000001);
+            CHANNEL.write(0b00000001);
         }
 		1 => {
-            core::ptr::write_volatile(CHANNEL, 0b00000010);
+            CHANNEL.write(0b00000010);
         }
 		2 => {
-            core::ptr::write_volatile(CHANNEL, 0b00000100);
+            CHANNEL.write(0b00000100);
         }
 		3 => {
-            core::ptr::write_volatile(CHANNEL, 0b00001000);
+            CHANNEL.write(0b00001000);
         }
 		4 => {
-            core::ptr::write_volatile(CHANNEL, 0b00010000);
+            CHANNEL.write(0b00010000);
         }
 		5 =>{
-            core::ptr::write_volatile(CHANNEL, 0b00100000)
+            CHANNEL.write(0b00100000)
         }
 		6 => {
-            core::ptr::write_volatile(CHANNEL, 0b01000000);
+            CHANNEL.write(0b01000000);
         }
 		7 => {
-            core::ptr::write_volatile(CHANNEL, 0b10000000);
+            CHANNEL.write(0b10000000);
         }
 		_ => {
-            core::ptr::write_volatile(CHANNEL, 0);
+            CHANNEL.write(0);
         }
 	}
 }
 
-/// A helper one-liner for mutating raw pointers at given address.
-/// You shouldn't need to change this.
-///
-/// # How to use
-///
-/// Set a bit to high.
-///
-/// ```ignore
-/// mutate_ptr(ADDR, |x| x | 1);
-/// ```
-///
-/// Flip bit's value.
-///
-/// ```ignore
-/// mutate_ptr(ADDR, |x| x ^ 1);
-/// ```
-///
-/// TODO: does this function have to be unsafe?
-unsafe fn mutate_ptr<A, F>(addr: *mut A, mutate_fn: F)
-where
-    F: FnOnce(A) -> A,
-{
-    let prev = core::ptr::read_volatile(addr);
-    let new = mutate_fn(prev);
-    core::ptr::write_volatile(addr, new);
+#[cfg(all(test, feature = "sim"))]
+mod tests {
+    use super::*;
+    use crate::control::{LAT, RST, SB, SCK, SDA};
+    use crate::register::{take_log, Access, SERIAL};
+    use crate::CONTROL_ADDRESS;
+    use std::vec::Vec;
+
+    /// Values written to the control register since the last call, which must not have read it.
+    fn control_writes() -> Vec<u8> {
+        let log = take_log();
+        let control = log.iter().filter(|record| record.address == CONTROL_ADDRESS);
+        assert!(control.clone().all(|record| record.access == Access::Write));
+        control.map(|record| record.value as u8).collect()
+    }
+
+    /// The SDA bit at every rising edge of SCK.
+    fn shifted_bits(writes: &[u8]) -> Vec<bool> {
+        let mut previous = 0;
+        let mut bits = Vec::new();
+        for &value in writes {
+            if value & SCK != 0 && previous & SCK == 0 {
+                bits.push(value & SDA != 0);
+            }
+            previous = value;
+        }
+        bits
+    }
+
+    /// Count of rising edges of `signal`.
+    fn pulses(writes: &[u8], signal: u8) -> usize {
+        writes.windows(2).filter(|pair| pair[0] & signal == 0 && pair[1] & signal != 0).count()
+    }
+
+    #[test]
+    fn setup_resets_and_loads_full_dot_correction() {
+        let _serial = SERIAL.lock().unwrap();
+        take_log();
+        setup_led_matrix();
+        let writes = control_writes();
+
+        assert_eq!(writes[..2], [0, RST]);
+        // 6 bits for each of the 24 channels, all ones, shifted in with the dot correction bank selected
+        let bits = shifted_bits(&writes);
+        assert_eq!(bits.len(), 6 * 24);
+        assert!(bits.iter().all(|&bit| bit));
+        assert!(writes.iter().filter(|&&value| value & SCK != 0).all(|&value| value & SB == 0));
+        // Ends in the PWM bank, with everything else idle
+        assert_eq!(writes.last(), Some(&(RST | SB)));
+        assert_eq!(pulses(&writes, LAT), 0);
+    }
+
+    #[test]
+    fn run_shifts_out_one_line_and_latches_it() {
+        let _serial = SERIAL.lock().unwrap();
+        setup_led_matrix();
+        unsafe {
+            for y in 0..8 {
+                set_pixel(2, y, 0, 0, 0);
+            }
+            set_pixel(2, 1, 255, 0, 128);
+            flip();
+            start_refresh();
+            take_log();
+            run(2, 0);
+        }
+        let writes = control_writes();
+
+        // Pixel after pixel, blue, green and red, most significant bit first
+        let mut expected = [0; LINE_BYTES];
+        expected[3..6].copy_from_slice(&[slot_values(128)[0], 0, slot_values(255)[0]]);
+        let bytes: Vec<u8> = shifted_bits(&writes)
+            .chunks(8)
+            .map(|bits| bits.iter().fold(0, |byte, &bit| byte << 1 | u8::from(bit)))
+            .collect();
+        assert_eq!(bytes, expected);
+        assert_eq!(pulses(&writes, LAT), 1);
+        // Two writes per bit, the one leaving SCK low and the latch pulse, and no reads. It was 1156 accesses when
+        // every signal change read the register back first.
+        assert_eq!(writes.len(), 2 * 8 * LINE_BYTES + 3);
+        assert!(writes.iter().all(|&value| value & (RST | SB) == RST | SB));
+        assert_eq!(writes.last(), Some(&(RST | SB)));
+
+        // The same line again is already on the outputs of the DM163
+        unsafe { run(2, 0) };
+        assert_eq!(control_writes(), []);
+    }
 }
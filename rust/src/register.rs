@@ -0,0 +1,198 @@
+//! Access to memory-mapped peripheral registers.
+//!
+//! Game code talks to the hardware only through the [Register] trait.
+//! On the board the registers are `Mmio`, which does volatile reads and writes at a physical address.
+//! In the simulator build they are `Mock`, which keeps the value in memory and records every access,
+//! so that a host can check exactly what was read and written, and in which order.
+
+#[cfg(not(feature = "sim"))]
+use core::marker::PhantomData;
+
+#[cfg(feature = "sim")]
+use core::cell::Cell;
+#[cfg(feature = "sim")]
+use std::sync::Mutex;
+
+/// A memory-mapped register holding a value of type `T`.
+pub trait Register<T: Copy> {
+    /// Read the current value of the register.
+    fn read(&self) -> T;
+
+    /// Write a new value into the register.
+    fn write(&self, value: T);
+
+    /// Read the register, pass the value through `mutate_fn` and write the result back.
+    ///
+    /// # How to use
+    ///
+    /// Set a bit to high.
+    ///
+    /// ```ignore
+    /// REGISTER.modify(|x| x | 1);
+    /// ```
+    ///
+    /// Flip bit's value.
+    ///
+    /// ```ignore
+    /// REGISTER.modify(|x| x ^ 1);
+    /// ```
+    fn modify<F>(&self, mutate_fn: F)
+    where
+        F: FnOnce(T) -> T,
+    {
+        let prev = self.read();
+        self.write(mutate_fn(prev));
+    }
+}
+
+/// A register at a physical address, accessed with volatile reads and writes.
+#[cfg(not(feature = "sim"))]
+pub struct Mmio<T> {
+    address: usize,
+    _value: PhantomData<T>,
+}
+
+#[cfg(not(feature = "sim"))]
+impl<T> Mmio<T> {
+    /// Create a handle to the register at `address`.
+    ///
+    /// # Safety
+    ///
+    /// `address` must be the address of a readable and writable register of type `T`.
+    pub const unsafe fn new(address: usize) -> Self {
+        Mmio {
+            address,
+            _value: PhantomData,
+        }
+    }
+}
+
+// The handle is only an address, the hardware register behind it is shared anyway.
+#[cfg(not(feature = "sim"))]
+unsafe impl<T> Sync for Mmio<T> {}
+
+#[cfg(not(feature = "sim"))]
+impl<T: Copy> Register<T> for Mmio<T> {
+    fn read(&self) -> T {
+        unsafe { core::ptr::read_volatile(self.address as *const T) }
+    }
+
+    fn write(&self, value: T) {
+        unsafe { core::ptr::write_volatile(self.address as *mut T, value) }
+    }
+}
+
+/// Register widths that a [Mock] can store and record.
+#[cfg(feature = "sim")]
+pub trait Word: Copy {
+    fn to_u32(self) -> u32;
+    fn from_u32(value: u32) -> Self;
+}
+
+#[cfg(feature = "sim")]
+impl Word for u8 {
+    fn to_u32(self) -> u32 {
+        self.into()
+    }
+
+    fn from_u32(value: u32) -> Self {
+        value as u8
+    }
+}
+
+#[cfg(feature = "sim")]
+impl Word for u32 {
+    fn to_u32(self) -> u32 {
+        self
+    }
+
+    fn from_u32(value: u32) -> Self {
+        value
+    }
+}
+
+/// Kind of a recorded register access.
+#[cfg(feature = "sim")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+}
+
+/// One recorded register access.
+#[cfg(feature = "sim")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Record {
+    /// Address of the register as given to [Mock::new].
+    pub address: usize,
+    pub access: Access,
+    /// Value read or written.
+    pub value: u32,
+}
+
+/// Accesses to all mock registers, in the order they happened.
+#[cfg(feature = "sim")]
+static LOG: Mutex<Vec<Record>> = Mutex::new(Vec::new());
+
+/// The log and the statics the logged code works on are shared, so the tests using them take turns on this.
+#[cfg(all(test, feature = "sim"))]
+pub static SERIAL: Mutex<()> = Mutex::new(());
+
+/// Take all accesses recorded since the previous call.
+#[cfg(feature = "sim")]
+pub fn take_log() -> Vec<Record> {
+    core::mem::take(&mut *LOG.lock().unwrap())
+}
+
+/// An in-memory register that records every read and write into a shared log.
+/// See [take_log].
+#[cfg(feature = "sim")]
+pub struct Mock<T> {
+    address: usize,
+    value: Cell<u32>,
+    _value: core::marker::PhantomData<T>,
+}
+
+#[cfg(feature = "sim")]
+impl<T> Mock<T> {
+    /// Create a mock register that stands in for the register at `address`.
+    /// It reads as zero until written.
+    ///
+    /// # Safety
+    ///
+    /// Always safe, but `unsafe` like `Mmio::new` so that the two can be swapped at the declaration.
+    pub const unsafe fn new(address: usize) -> Self {
+        Mock {
+            address,
+            value: Cell::new(0),
+            _value: core::marker::PhantomData,
+        }
+    }
+
+    fn record(&self, access: Access, value: u32) {
+        LOG.lock().unwrap().push(Record {
+            address: self.address,
+            access,
+            value,
+        });
+    }
+}
+
+// The simulator calls the interrupt handlers from a single thread, just like the single-core board.
+#[cfg(feature = "sim")]
+unsafe impl<T> Sync for Mock<T> {}
+
+#[cfg(feature = "sim")]
+impl<T: Word> Register<T> for Mock<T> {
+    fn read(&self) -> T {
+        let value = self.value.get();
+        self.record(Access::Read, value);
+        T::from_u32(value)
+    }
+
+    fn write(&self, value: T) {
+        let value = value.to_u32();
+        self.value.set(value);
+        self.record(Access::Write, value);
+    }
+}
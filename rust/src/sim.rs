@@ -0,0 +1,314 @@
+//! Host-side simulator backend.
+//!
+//! Lets the game run on a Linux host without the Pynq board. Build with `--features sim`.
+//!
+//! The physical registers of the Colors Shield, the LEDs and the inputs are in-memory mock registers
+//! (see [crate::register::Mock]), the Xilinx BSP functions used by the game get host stand-ins, and [run] drives
+//! the interrupt handlers from a host timer loop while feeding keyboard input to `button_handler`.
+//! The terminal shows what the [dm163] model of the Colors Shield reconstructs from the register writes.
+//! Run with `--vcd <file>` to also dump the shield signals as a [vcd] waveform, and with `--seed <number>` to
+//! replay games with the random numbers of a seed logged earlier.
+
+use std::fs::File;
+use std::io::{BufWriter, Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::mpsc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+mod dm163;
+mod vcd;
+
+use dm163::Dm163;
+use vcd::Vcd;
+
+use crate::pixel::{get_pixel, lines_sent_per_second, SLOTS};
+use crate::register::{self, Register};
+use crate::{button_handler, tick_handler, tick_handler_1, LED_ADDRESS, REFRESH_FREQUENCY};
+
+/// How often the terminal picture is redrawn.
+const DRAW_INTERVAL: Duration = Duration::from_millis(33);
+
+/// Rate of the game logic interrupt (`tick_handler_1`), changed by [interrupt::change_freq].
+static GAME_FREQUENCY: AtomicU32 = AtomicU32::new(10);
+/// Rate of the refresh interrupt (`tick_handler`), changed by [interrupt::set_slot_interval].
+static REFRESH_RATE: AtomicU32 = AtomicU32::new(REFRESH_FREQUENCY);
+
+/// Host stand-ins for the parts of the Xilinx BSP the game uses.
+#[allow(non_snake_case)]
+pub mod xil {
+    pub type XTtcPs = u32;
+    pub type XGpioPs = u32;
+
+    /// There is only one thread calling the handlers, so there is nothing to mask.
+    pub unsafe fn Xil_ExceptionEnable() {}
+
+    pub unsafe fn Xil_ExceptionDisable() {}
+
+    pub unsafe fn XTtcPs_GetInterruptStatus(_instance: *mut XTtcPs) -> u32 {
+        0
+    }
+
+    pub unsafe fn XTtcPs_ClearInterruptStatus(_instance: *mut XTtcPs, _mask: u32) {}
+
+    pub unsafe fn usleep(useconds: u32) -> i32 {
+        std::thread::sleep(std::time::Duration::from_micros(useconds.into()));
+        0
+    }
+}
+
+/// Host stand-in for the board interrupt setup.
+pub mod interrupt {
+    use core::sync::atomic::Ordering;
+
+    /// The simulator loop calls the handlers itself, so there is nothing to register.
+    pub fn init() {}
+
+    /// Changes the rate at which the simulator calls `tick_handler_1`.
+    pub fn change_freq(freq: u32) {
+        super::GAME_FREQUENCY.store(freq, Ordering::Relaxed);
+    }
+
+    /// Calls `tick_handler` next when slot `slot` of the line is over.
+    pub unsafe fn set_slot_interval(_ttc: *mut super::xil::XTtcPs, slot: usize) {
+        let rate = crate::REFRESH_FREQUENCY * 4 / crate::pixel::SLOT_WEIGHTS[slot];
+        super::REFRESH_RATE.store(rate, Ordering::Relaxed);
+    }
+}
+
+/// Prints a line to stderr, which stands in for the UART.
+/// Redirect it (e.g. `2> sim.log`) to keep it from mixing with the matrix on the terminal.
+#[macro_export]
+macro_rules! println64 {
+    ($($arg:tt)*) => {
+        std::eprintln!($($arg)*)
+    };
+}
+
+/// Rust port of the assembly blinker, which walks one lit LED back and forth on the board LEDs.
+pub unsafe fn blinker() -> u32 {
+    static mut DIRECTION_LEFT: bool = true;
+
+    let mut leds = LED_ADDRESS.read();
+    if leds == 0 {
+        leds = 1;
+    } else if DIRECTION_LEFT {
+        leds <<= 1;
+        if leds > 7 {
+            DIRECTION_LEFT = false;
+        }
+    } else {
+        leds >>= 1;
+        if leds <= 1 {
+            DIRECTION_LEFT = true;
+        }
+    }
+    LED_ADDRESS.write(leds);
+    0
+}
+
+/// Maps a key to the `status` code the GPIO interrupt would pass to `button_handler`.
+fn key_to_status(key: u8) -> Option<u32> {
+    match key {
+        b'd' => Some(0x01), // BTN0, move right
+        b'p' => Some(0x02), // BTN1, pause
+        b' ' | b'w' => Some(0x04), // BTN2, shoot
+        b'a' => Some(0x08), // BTN3, move left
+        b'0' => Some(0x10), // SW0, restart
+        b'1' => Some(0x20), // SW1, game speed
+        _ => None,
+    }
+}
+
+/// Switches the terminal between line-buffered and unbuffered input without echo.
+fn set_raw_terminal(raw: bool) {
+    let mode: &[&str] = if raw { &["-icanon", "-echo"] } else { &["icanon", "echo"] };
+    // Not fatal if it fails, keys then arrive after enter.
+    let _ = Command::new("stty").args(mode).stdin(Stdio::inherit()).stderr(Stdio::null()).status();
+}
+
+/// Checks that the lines just lit by the shield show what `DOTS` holds for them.
+/// Reports only the first difference of each line, the same bug would otherwise flood the log.
+fn verify(shield: &Dm163, reported: &mut u8) {
+    for x in 0..8 {
+        if shield.open_lines() & (1 << x) == 0 || *reported & (1 << x) != 0 {
+            continue;
+        }
+        for y in 0..8 {
+            let shown = shield.pixel(x, y);
+            let expected = unsafe { get_pixel(x, y) };
+            if shown != expected {
+                println64!("Shield shows {:?} at ({}, {}), DOTS has {:?}", shown, x, y, expected);
+                *reported |= 1 << x;
+                break;
+            }
+        }
+    }
+}
+
+/// Draws the matrix and the board LEDs on the terminal.
+fn draw(shield: &Dm163) {
+    let mut out = String::new();
+    out.push_str("\x1b[H");
+    for y in 0..8 {
+        for x in 0..8 {
+            let (r, g, b) = shield.pixel(x, y);
+            out.push_str(&format!("\x1b[48;2;{};{};{}m  ", r, g, b));
+        }
+        out.push_str("\x1b[0m\r\n");
+    }
+
+    let leds = LED_ADDRESS.read();
+    out.push_str("\r\nLEDs ");
+    for i in (0..4).rev() {
+        out.push(if leds & (1 << i) != 0 { '*' } else { '.' });
+    }
+    out.push_str("\r\n\r\na/d: move  space: shoot  p: pause  0: restart  1: speed  q: quit\r\n");
+
+    let mut stdout = std::io::stdout();
+    let _ = stdout.write_all(out.as_bytes());
+    let _ = stdout.flush();
+}
+
+/// Where the register accesses of the simulated hardware go.
+struct Bus {
+    shield: Dm163,
+    vcd: Option<Vcd<BufWriter<File>>>,
+}
+
+impl Bus {
+    /// Run the accesses made since the last call, by a handler that started at `time`, through the shield model
+    /// and the waveform dump. Returns the number of accesses.
+    fn sync(&mut self, time: Duration) -> usize {
+        let records = register::take_log();
+        for record in &records {
+            self.shield.feed(record);
+        }
+        if let Some(vcd) = &mut self.vcd {
+            if let Err(error) = vcd.dump(time, &records) {
+                println64!("VCD dump stopped: {}", error);
+                self.vcd = None;
+            }
+        }
+        records.len()
+    }
+}
+
+/// Seed for the random numbers of a new game: the one given with `--seed <number>`, or else taken from the clock
+/// like the board takes it from the global timer.
+pub fn new_seed() -> u32 {
+    let seed = std::env::args().skip_while(|arg| arg != "--seed").nth(1);
+    match seed.map(|seed| seed.parse()) {
+        Some(Ok(seed)) => seed,
+        Some(Err(_)) => {
+            println64!("--seed needs a number");
+            0
+        }
+        None => SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.subsec_nanos()),
+    }
+}
+
+/// Open the VCD file given with `--vcd <file>` on the command line, if any.
+fn open_vcd() -> Option<Vcd<BufWriter<File>>> {
+    let path = std::env::args().skip_while(|arg| arg != "--vcd").nth(1)?;
+    match File::create(&path).and_then(|file| Vcd::new(BufWriter::new(file))) {
+        Ok(vcd) => {
+            println64!("Dumping shield signals to {}", path);
+            Some(vcd)
+        }
+        Err(error) => {
+            println64!("Can't dump to {}: {}", path, error);
+            None
+        }
+    }
+}
+
+/// Runs the simulator until `q` is pressed.
+///
+/// Calls `tick_handler` at the refresh rate and `tick_handler_1` at the game rate, like the two board timers do,
+/// and forwards key presses to `button_handler`.
+/// After every refresh the register writes are run through the shield model and the result checked against `DOTS`.
+/// On quit the average number of register accesses per refresh is printed, a measure of the time the board spends
+/// in `tick_handler`, along with the lines shifted out to the DM163 in the last second.
+/// Simulated time runs with the wall clock, interrupts happen at the time their timer tick was due.
+pub fn run() {
+    let mut bus = Bus {
+        shield: Dm163::new(),
+        vcd: open_vcd(),
+    };
+    let mut reported = 0;
+    // Register accesses made by the refreshes, the time spent in `tick_handler` on the board
+    let (mut refreshes, mut refresh_accesses) = (0u64, 0);
+
+    // Let the model see the setup done before the loop.
+    bus.sync(Duration::ZERO);
+
+    set_raw_terminal(true);
+    print!("\x1b[2J");
+
+    let (keys_tx, keys) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut stdin = std::io::stdin().lock();
+        let mut key = [0u8; 1];
+        while let Ok(1) = stdin.read(&mut key) {
+            if keys_tx.send(key[0]).is_err() {
+                break;
+            }
+        }
+    });
+
+    let start = Instant::now();
+    let mut next_refresh = start;
+    let mut next_game_tick = start;
+    let mut next_draw = start;
+
+    'sim: loop {
+        let now = Instant::now();
+
+        // Handle the due timer ticks in the order they were due.
+        while next_refresh.min(next_game_tick) <= now {
+            if next_refresh <= next_game_tick {
+                unsafe { tick_handler(core::ptr::null_mut()) };
+                refresh_accesses += bus.sync(next_refresh - start) as u64;
+                refreshes += 1;
+                verify(&bus.shield, &mut reported);
+                next_refresh += Duration::from_secs(1) / REFRESH_RATE.load(Ordering::Relaxed).max(1);
+            } else {
+                unsafe { tick_handler_1(core::ptr::null_mut()) };
+                bus.sync(next_game_tick - start);
+                next_game_tick += Duration::from_secs(1) / GAME_FREQUENCY.load(Ordering::Relaxed).max(1);
+            }
+        }
+
+        while let Ok(key) = keys.try_recv() {
+            if key == b'q' {
+                break 'sim;
+            }
+            if let Some(status) = key_to_status(key) {
+                unsafe { button_handler(core::ptr::null_mut(), 2, status) };
+                bus.sync(now - start);
+            }
+        }
+
+        if next_draw <= now {
+            draw(&bus.shield);
+            next_draw = now + DRAW_INTERVAL;
+        }
+
+        std::thread::sleep(Duration::from_millis(1));
+    }
+
+    if let Some(vcd) = &mut bus.vcd {
+        if let Err(error) = vcd.flush() {
+            println64!("VCD dump incomplete: {}", error);
+        }
+    }
+    set_raw_terminal(false);
+    if let Some(accesses) = refresh_accesses.checked_div(refreshes) {
+        println64!("Register accesses per refresh: {}", accesses);
+    }
+    if start.elapsed() >= Duration::from_secs(1) {
+        let lines = unsafe { lines_sent_per_second() };
+        println64!("Lines sent/s: {} of {}", lines, REFRESH_FREQUENCY * SLOTS as u32);
+    }
+}
@@ -0,0 +1,191 @@
+//! Software model of the Colors Shield: the DM163 column driver and the M54564 row driver.
+//!
+//! The model is fed the register writes recorded by the mock registers and follows the signals the same way the
+//! chips do, so the image it reconstructs is what the LEDs would actually show.
+//! Comparing it to `DOTS` catches bit-order, color-order and protocol bugs in the scan-out code.
+//!
+//! The shield wires the DM163 outputs so that the first byte shifted in for a line ends up on OUT23:
+//! OUT23, OUT22 and OUT21 are the blue, green and red LED of pixel 0, OUT20 to OUT18 those of pixel 1, and so on.
+
+use crate::control::{LAT, RST, SB, SCK, SDA};
+use crate::register::{Access, Record};
+use crate::{CHANNEL_ADDRESS, CONTROL_ADDRESS};
+
+/// Number of DM163 output channels.
+const OUTPUTS: usize = 24;
+
+/// Dot correction value of full brightness.
+const FULL_DOT_CORRECTION: u16 = 0x3f;
+
+/// State of the two driver chips and the picture they have put on the matrix.
+pub struct Dm163 {
+    /// Last value written to the control register.
+    control: u8,
+    /// 8-bit shift register, `[0]` is OUT0, which receives the bits from SDA.
+    pwm_shift: [u8; OUTPUTS],
+    /// 6-bit shift register, same order as `pwm_shift`.
+    dot_correction_shift: [u8; OUTPUTS],
+    /// Latched 8-bit brightness of each output.
+    pwm: [u8; OUTPUTS],
+    /// Loaded 6-bit dot correction of each output.
+    dot_correction: [u8; OUTPUTS],
+    /// Row driver inputs, one bit per line.
+    lines: u8,
+    /// Last color shown on each LED as `[line][pixel] = [r, g, b]`.
+    frame: [[[u8; 3]; 8]; 8],
+}
+
+impl Dm163 {
+    pub const fn new() -> Self {
+        Dm163 {
+            control: 0,
+            pwm_shift: [0; OUTPUTS],
+            dot_correction_shift: [0; OUTPUTS],
+            pwm: [0; OUTPUTS],
+            dot_correction: [0; OUTPUTS],
+            lines: 0,
+            frame: [[[0; 3]; 8]; 8],
+        }
+    }
+
+    /// Follow one register access.
+    /// Reads and accesses to other registers are ignored.
+    pub fn feed(&mut self, record: &Record) {
+        if record.access != Access::Write {
+            return;
+        }
+        match record.address {
+            CONTROL_ADDRESS => self.control_changed(record.value as u8),
+            CHANNEL_ADDRESS => {
+                self.lines = record.value as u8;
+                self.show();
+            }
+            _ => {}
+        }
+    }
+
+    /// Lines currently switched on by the row driver, one bit per line.
+    pub fn open_lines(&self) -> u8 {
+        self.lines
+    }
+
+    /// The color of one LED as `(r, g, b)`, the way it was last lit.
+    /// Coordinates are the same as for `set_pixel`, `x` being the line.
+    pub fn pixel(&self, x: usize, y: usize) -> (u8, u8, u8) {
+        let [r, g, b] = self.frame[x][y];
+        (r, g, b)
+    }
+
+    fn control_changed(&mut self, control: u8) {
+        let rising = control & !self.control;
+        self.control = control;
+
+        if control & RST == 0 {
+            // Held in reset, shift registers and outputs are cleared.
+            self.pwm_shift = [0; OUTPUTS];
+            self.dot_correction_shift = [0; OUTPUTS];
+            self.pwm = [0; OUTPUTS];
+            self.show();
+            return;
+        }
+
+        if rising & SCK != 0 {
+            let bit = u8::from(control & SDA != 0);
+            if control & SB != 0 {
+                shift_in(&mut self.pwm_shift, bit, 8);
+            } else {
+                shift_in(&mut self.dot_correction_shift, bit, 6);
+            }
+        }
+
+        // The dot correction bank is loaded by a latch while it is selected, or when the PWM bank is selected.
+        if rising & LAT != 0 {
+            if control & SB != 0 {
+                self.pwm = self.pwm_shift;
+            } else {
+                self.dot_correction = self.dot_correction_shift;
+            }
+            self.show();
+        }
+        if rising & SB != 0 {
+            self.dot_correction = self.dot_correction_shift;
+            self.show();
+        }
+    }
+
+    /// Put the latched outputs on every open line.
+    fn show(&mut self) {
+        for line in 0..8 {
+            if self.lines & (1 << line) == 0 {
+                continue;
+            }
+            for pixel in 0..8 {
+                let blue = OUTPUTS - 1 - 3 * pixel;
+                let [b, g, r] = [blue, blue - 1, blue - 2].map(|output| self.brightness(output));
+                self.frame[line][pixel] = [r, g, b];
+            }
+        }
+    }
+
+    /// Brightness of one output, PWM value scaled by its dot correction.
+    fn brightness(&self, output: usize) -> u8 {
+        (u16::from(self.pwm[output]) * u16::from(self.dot_correction[output]) / FULL_DOT_CORRECTION) as u8
+    }
+}
+
+/// Shift `bit` into OUT0 of a chain of `width`-bit registers, moving every other bit one step towards OUT23.
+fn shift_in(chain: &mut [u8; OUTPUTS], bit: u8, width: u32) {
+    let mask = ((1u16 << width) - 1) as u8;
+    for output in (1..OUTPUTS).rev() {
+        chain[output] = ((chain[output] << 1) | (chain[output - 1] >> (width - 1))) & mask;
+    }
+    chain[0] = ((chain[0] << 1) | bit) & mask;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pixel::{flip, open_line, run, set_pixel, setup_led_matrix, start_refresh};
+    use crate::register::{take_log, SERIAL};
+    use game::{Canvas, Framebuffer};
+
+    #[test]
+    fn shows_what_the_scan_out_was_given() {
+        let _serial = SERIAL.lock().unwrap();
+        // Only full and no brightness, which every slot of the line shows the same
+        let mut picture = Framebuffer::new();
+        for x in 0..8 {
+            for y in 0..8 {
+                let on = |bit: usize| if (x + y) >> bit & 1 != 0 { 255 } else { 0 };
+                picture.set_pixel(x, y, on(0), on(1), on(2));
+            }
+        }
+
+        let mut shield = Dm163::new();
+        take_log();
+        setup_led_matrix();
+        unsafe {
+            for x in 0..8 {
+                for y in 0..8 {
+                    let (r, g, b) = picture.pixel(x, y);
+                    set_pixel(x, y, r, g, b);
+                }
+            }
+            flip();
+            start_refresh();
+            for line in 0..8 {
+                open_line(99);
+                run(line, 0);
+                open_line(line as u8);
+                take_log().iter().for_each(|record| shield.feed(record));
+                assert_eq!(shield.open_lines(), 1 << line);
+            }
+        }
+
+        for x in 0..8 {
+            for y in 0..8 {
+                assert_eq!(shield.pixel(x, y), picture.pixel(x, y), "at ({}, {})", x, y);
+            }
+        }
+    }
+}
@@ -0,0 +1,183 @@
+//! Value Change Dump (VCD) export of the Colors Shield signals, for viewing in GTKWave or similar.
+//!
+//! Every change on the control lines (RST, LAT, SB, SCK, SDA) and the line select register is dumped.
+//! Timestamps come from the simulated timer: each interrupt starts at the time its timer tick was due,
+//! and the register accesses made by the handler follow it [ACCESS_TIME_NS] apart.
+
+use std::io::{self, Write};
+use std::time::Duration;
+
+use crate::control::{LAT, RST, SB, SCK, SDA};
+use crate::register::{Access, Record};
+use crate::{CHANNEL_ADDRESS, CONTROL_ADDRESS};
+
+/// Assumed duration of one register access over the AXI GPIO.
+pub const ACCESS_TIME_NS: u64 = 100;
+
+/// Control signals in dump order, with their VCD identifiers.
+const SIGNALS: [(u8, &str, char); 5] = [
+    (RST, "rst", '!'),
+    (LAT, "lat", '"'),
+    (SB, "sb", '#'),
+    (SCK, "sck", '$'),
+    (SDA, "sda", '%'),
+];
+
+/// VCD identifier of the line select register.
+const CHANNEL_ID: char = '&';
+
+/// Writes register accesses into a VCD file.
+pub struct Vcd<W: Write> {
+    out: W,
+    /// Time of the last dumped change, in nanoseconds.
+    time: u64,
+    control: u8,
+    channel: u8,
+}
+
+impl<W: Write> Vcd<W> {
+    /// Write the VCD header and the initial values of all signals, which are low.
+    pub fn new(mut out: W) -> io::Result<Self> {
+        writeln!(out, "$version EmbeddedAlienShooting simulator $end")?;
+        writeln!(out, "$timescale 1ns $end")?;
+        writeln!(out, "$scope module colors_shield $end")?;
+        for (_, name, id) in SIGNALS {
+            writeln!(out, "$var wire 1 {} {} $end", id, name)?;
+        }
+        writeln!(out, "$var wire 8 {} channel $end", CHANNEL_ID)?;
+        writeln!(out, "$upscope $end")?;
+        writeln!(out, "$enddefinitions $end")?;
+        writeln!(out, "#0")?;
+        writeln!(out, "$dumpvars")?;
+        for (_, _, id) in SIGNALS {
+            writeln!(out, "0{}", id)?;
+        }
+        writeln!(out, "b0 {}", CHANNEL_ID)?;
+        writeln!(out, "$end")?;
+
+        Ok(Vcd {
+            out,
+            time: 0,
+            control: 0,
+            channel: 0,
+        })
+    }
+
+    /// Dump the changes made by `records`, the accesses of one interrupt that started at `start`.
+    pub fn dump(&mut self, start: Duration, records: &[Record]) -> io::Result<()> {
+        let mut time = start.as_nanos() as u64;
+        for record in records {
+            if record.access == Access::Write {
+                match record.address {
+                    CONTROL_ADDRESS => self.control_changed(time, record.value as u8)?,
+                    CHANNEL_ADDRESS => self.channel_changed(time, record.value as u8)?,
+                    _ => {}
+                }
+            }
+            time += ACCESS_TIME_NS;
+        }
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+
+    fn control_changed(&mut self, time: u64, control: u8) -> io::Result<()> {
+        let changed = control ^ self.control;
+        if changed == 0 {
+            return Ok(());
+        }
+        self.timestamp(time)?;
+        for (bit, _, id) in SIGNALS {
+            if changed & bit != 0 {
+                writeln!(self.out, "{}{}", u8::from(control & bit != 0), id)?;
+            }
+        }
+        self.control = control;
+        Ok(())
+    }
+
+    fn channel_changed(&mut self, time: u64, channel: u8) -> io::Result<()> {
+        if channel == self.channel {
+            return Ok(());
+        }
+        self.timestamp(time)?;
+        writeln!(self.out, "b{:b} {}", channel, CHANNEL_ID)?;
+        self.channel = channel;
+        Ok(())
+    }
+
+    /// Start a new time step, unless changes are already being dumped at or after `time`.
+    /// Timestamps in a VCD must increase, so a late change is dumped at the current time.
+    fn timestamp(&mut self, time: u64) -> io::Result<()> {
+        if time <= self.time {
+            return Ok(());
+        }
+        self.time = time;
+        writeln!(self.out, "#{}", time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pixel::{flip, open_line, run, set_pixel, setup_led_matrix, start_refresh};
+    use crate::register::{take_log, SERIAL};
+
+    /// Dump of the setup and of the first line scanned out, recorded from the mock registers.
+    /// The line starts at 1 µs, while the accesses of the setup still go on, so all of its changes are late.
+    fn recorded_dump() -> String {
+        let _serial = SERIAL.lock().unwrap();
+        take_log();
+        let mut vcd = Vcd::new(Vec::new()).unwrap();
+        setup_led_matrix();
+        vcd.dump(Duration::ZERO, &take_log()).unwrap();
+        unsafe {
+            set_pixel(0, 0, 255, 255, 255);
+            flip();
+            start_refresh();
+            take_log();
+            open_line(99);
+            run(0, 0);
+            open_line(0);
+        }
+        vcd.dump(Duration::from_micros(1), &take_log()).unwrap();
+        String::from_utf8(vcd.out).unwrap()
+    }
+
+    #[test]
+    fn header_declares_every_signal_once() {
+        let dump = recorded_dump();
+        let (header, _) = dump.split_once("$enddefinitions $end\n").unwrap();
+        assert!(header.starts_with("$version "));
+        assert!(header.contains("$timescale 1ns $end\n"));
+        let ids: Vec<&str> = header
+            .lines()
+            .filter_map(|line| line.strip_prefix("$var wire "))
+            .map(|var| var.split(' ').nth(1).unwrap())
+            .collect();
+        assert_eq!(ids, ["!", "\"", "#", "$", "%", "&"]);
+    }
+
+    #[test]
+    fn changes_use_declared_ids_at_increasing_times() {
+        let dump = recorded_dump();
+        let (_, changes) = dump.split_once("$enddefinitions $end\n").unwrap();
+        let mut times = Vec::new();
+        for line in changes.lines().filter(|&line| line != "$dumpvars" && line != "$end") {
+            if let Some(time) = line.strip_prefix('#') {
+                times.push(time.parse::<u64>().unwrap());
+            } else if let Some(vector) = line.strip_prefix('b') {
+                assert_eq!(vector.split_once(' ').map(|(_, id)| id), Some("&"), "{}", line);
+            } else {
+                assert!(matches!(line.as_bytes(), [b'0' | b'1', b'!'..=b'%']), "{}", line);
+            }
+        }
+        assert_eq!(times[0], 0);
+        assert!(times.windows(2).all(|pair| pair[0] < pair[1]), "{:?}", times);
+        // A step for every SCK edge of the dot correction, and the late changes of the line still dumped
+        assert!(times.len() > 2 * 6 * 24);
+        assert!(changes.ends_with("b1 &\n"));
+    }
+}